@@ -0,0 +1,79 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use veloquad::geometry::{covers, intersects};
+use veloquad::models::quad::Quad;
+use veloquad::models::rect::Rect;
+use veloquad::planner::{PlanOptions, Planner};
+use veloquad::quadtree::{build_quad, build_quad_iterative};
+
+/// Deterministic scatter of `count` small obstacles over an `n×n` grid.
+fn scattered_obstacles(n: i32, count: usize) -> Vec<Rect> {
+    let mut seed: u64 = 0x9e3779b97f4a7c15;
+    let mut next = |bound: i32| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % bound as u64) as i32
+    };
+    (0..count)
+        .map(|_| Rect { x: next(n - 4), y: next(n - 4), w: 1 + next(3), h: 1 + next(3) })
+        .collect()
+}
+
+/// `build_quad` before pruning: every node rescans the full obstacle list.
+fn build_quad_unpruned(obstacles: &[Rect], x: i32, y: i32, size: i32) -> Quad {
+    if size <= 1 {
+        if obstacles.iter().any(|obs| intersects(obs, x, y, size)) {
+            return Quad::Blocked;
+        }
+        return Quad::Free(x, y, size);
+    }
+    if obstacles.iter().any(|obs| covers(obs, x, y, size)) {
+        return Quad::Blocked;
+    }
+    if !obstacles.iter().any(|obs| intersects(obs, x, y, size)) {
+        return Quad::Free(x, y, size);
+    }
+    let h = size / 2;
+    Quad::Split(Box::new([
+        build_quad_unpruned(obstacles, x, y + h, h),
+        build_quad_unpruned(obstacles, x + h, y + h, h),
+        build_quad_unpruned(obstacles, x, y, h),
+        build_quad_unpruned(obstacles, x + h, y, h),
+    ]))
+}
+
+fn bench_build(c: &mut Criterion) {
+    let obstacles = scattered_obstacles(512, 8000);
+
+    let mut group = c.benchmark_group("build_quad_dense_512");
+    group.sample_size(10);
+    group.bench_function("unpruned", |b| {
+        b.iter(|| build_quad_unpruned(black_box(&obstacles), 0, 0, 512))
+    });
+    group.bench_function("pruned", |b| b.iter(|| build_quad(black_box(&obstacles), 0, 0, 512)));
+    // The planner builds through `build_terrain_quad`, then adds the region graph
+    group.bench_function("planner", |b| {
+        b.iter(|| Planner::new(512, black_box(&obstacles), &PlanOptions::default()).unwrap())
+    });
+    group.finish();
+}
+
+/// Recursive vs iterative build across grid sizes, 2n obstacles each; the results
+/// set `quadtree::RECURSIVE_BUILD_LIMIT`.
+fn bench_build_by_size(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_quad_by_size");
+    group.sample_size(20);
+    for n in [64, 256, 1024, 4096, 16384] {
+        let obstacles = scattered_obstacles(n, 2 * n as usize);
+        group.bench_with_input(BenchmarkId::new("recursive", n), &obstacles, |b, obs| {
+            b.iter(|| build_quad(black_box(obs), 0, 0, n))
+        });
+        group.bench_with_input(BenchmarkId::new("iterative", n), &obstacles, |b, obs| {
+            b.iter(|| build_quad_iterative(black_box(obs), 0, 0, n))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_build, bench_build_by_size);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::collections::HashMap;
+use veloquad::algo::dijkstra_path;
+use veloquad::csr::{dijkstra_csr, CsrGraph};
+use veloquad::graph::build_graph;
+use veloquad::models::rect::Rect;
+use veloquad::quadtree::{build_quad, collect_free};
+
+/// Deterministic scatter of `count` small obstacles over an `n×n` grid.
+fn scattered_obstacles(n: i32, count: usize) -> Vec<Rect> {
+    let mut seed: u64 = 0x2545f4914f6cdd1d;
+    let mut next = |bound: i32| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % bound as u64) as i32
+    };
+    (0..count)
+        .map(|_| Rect { x: next(n - 4), y: next(n - 4), w: 1 + next(3), h: 1 + next(3) })
+        .collect()
+}
+
+fn region_graph(n: i32, obstacles: &[Rect]) -> Vec<Vec<(usize, f64)>> {
+    let quad = build_quad(obstacles, 0, 0, n);
+    let mut centers = HashMap::new();
+    let mut id = 0;
+    collect_free(&quad, &mut id, &mut centers);
+    let mut graph = vec![vec![]; centers.len()];
+    let mut id = 0;
+    build_graph(&quad, &mut id, &mut graph, &centers);
+    graph
+}
+
+fn bench_search(c: &mut Criterion) {
+    let graph = region_graph(256, &scattered_obstacles(256, 2000));
+    let csr = CsrGraph::from_adjacency(&graph);
+    let (start, goal) = (0, graph.len() - 1);
+
+    let mut group = c.benchmark_group("dijkstra_256");
+    group.bench_function("nested_vec", |b| {
+        b.iter(|| dijkstra_path(black_box(&graph), start, goal))
+    });
+    group.bench_function("csr", |b| b.iter(|| dijkstra_csr(black_box(&csr), start, goal)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_search);
+criterion_main!(benches);
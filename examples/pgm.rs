@@ -0,0 +1,170 @@
+//! Plans across a PGM occupancy grid, as saved by ROS map servers, and writes the
+//! path back onto a copy of the image:
+//! `cargo run --example pgm <map.pgm> <out.pgm> <sx> <sy> <gx> <gy>`.
+//!
+//! Pixels darker than `THRESHOLD` are obstacles. Endpoints are pixel coordinates
+//! with the origin at the top-left corner, as in image viewers.
+
+use std::fs;
+
+use veloquad::models::rect::Rect;
+use veloquad::obstacles::merge_obstacles;
+use veloquad::planner::{PlanOptions, Planner};
+
+const THRESHOLD: u32 = 128;
+
+/// Grayscale image, row-major from the top-left corner.
+struct Pgm {
+    width: usize,
+    height: usize,
+    max: u32,
+    pixels: Vec<u32>,
+}
+
+/// Parses binary (`P5`) and ASCII (`P2`) PGM files with 8-bit or 16-bit samples.
+fn read_pgm(bytes: &[u8]) -> Result<Pgm, String> {
+    let mut pos = 0;
+    let mut token = || -> Result<String, String> {
+        loop {
+            while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if bytes.get(pos) != Some(&b'#') {
+                break;
+            }
+            while pos < bytes.len() && bytes[pos] != b'\n' {
+                pos += 1;
+            }
+        }
+        let start = pos;
+        while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        match &bytes[start..pos] {
+            [] => Err("truncated header".to_string()),
+            t => Ok(String::from_utf8_lossy(t).into_owned()),
+        }
+    };
+    let number = |t: String| t.parse::<u32>().map_err(|_| format!("invalid number {:?}", t));
+
+    let magic = token()?;
+    let width = number(token()?)? as usize;
+    let height = number(token()?)? as usize;
+    let max = number(token()?)?;
+    if max == 0 || max > 65535 {
+        return Err(format!("invalid maximum value {}", max));
+    }
+
+    let count = width * height;
+    let pixels = match magic.as_str() {
+        "P2" => (0..count).map(|_| token().and_then(number)).collect::<Result<Vec<_>, _>>()?,
+        "P5" => {
+            // A single whitespace byte separates the header from the samples
+            let data = &bytes[(pos + 1).min(bytes.len())..];
+            let wide = max > 255;
+            let needed = if wide { 2 * count } else { count };
+            if data.len() < needed {
+                return Err(format!("expected {} bytes of pixels, got {}", needed, data.len()));
+            }
+            if wide {
+                data.chunks(2).take(count).map(|c| u32::from(c[0]) << 8 | u32::from(c[1])).collect()
+            } else {
+                data[..count].iter().map(|&b| u32::from(b)).collect()
+            }
+        }
+        other => return Err(format!("unsupported format {:?}", other)),
+    };
+
+    Ok(Pgm {
+        width,
+        height,
+        max,
+        pixels,
+    })
+}
+
+/// Binary PGM with 8-bit samples.
+fn write_pgm(img: &Pgm) -> Vec<u8> {
+    let mut out = format!("P5\n{} {}\n255\n", img.width, img.height).into_bytes();
+    out.extend(img.pixels.iter().map(|&p| (p * 255 / img.max) as u8));
+    out
+}
+
+/// Obstacles of the square map holding the image: one rectangle per horizontal run
+/// of dark pixels, merged into blocks, plus the padding beyond the image. Image rows
+/// are flipped so that map `y` grows upwards.
+fn obstacles(img: &Pgm, n: i32) -> Vec<Rect> {
+    let mut runs = Vec::new();
+    for row in 0..img.height {
+        let y = (img.height - 1 - row) as i32;
+        let mut col = 0;
+        while col < img.width {
+            if img.pixels[row * img.width + col] * 255 / img.max >= THRESHOLD {
+                col += 1;
+                continue;
+            }
+            let start = col;
+            while col < img.width && img.pixels[row * img.width + col] * 255 / img.max < THRESHOLD {
+                col += 1;
+            }
+            runs.push(Rect { x: start as i32, y, w: (col - start) as i32, h: 1 });
+        }
+    }
+    let (w, h) = (img.width as i32, img.height as i32);
+    runs.push(Rect { x: w, y: 0, w: n - w, h: n });
+    runs.push(Rect { x: 0, y: h, w, h: n - h });
+    merge_obstacles(&runs)
+}
+
+/// Draws the polyline onto the image in mid gray, sampling every half pixel.
+fn draw_path(img: &mut Pgm, waypoints: &[(f64, f64)]) {
+    let gray = img.max / 2;
+    for leg in waypoints.windows(2) {
+        let ((x1, y1), (x2, y2)) = (leg[0], leg[1]);
+        let steps = ((x2 - x1).abs().max((y2 - y1).abs()) * 2.0).ceil().max(1.0) as usize;
+        for s in 0..=steps {
+            let t = s as f64 / steps as f64;
+            let (x, y) = (x1 + t * (x2 - x1), y1 + t * (y2 - y1));
+            let (col, row) = (x.floor() as i64, img.height as i64 - 1 - y.floor() as i64);
+            if (0..img.width as i64).contains(&col) && (0..img.height as i64).contains(&row) {
+                img.pixels[row as usize * img.width + col as usize] = gray;
+            }
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() != 6 {
+        eprintln!("Usage: cargo run --example pgm <map.pgm> <out.pgm> <sx> <sy> <gx> <gy>");
+        std::process::exit(2);
+    }
+    let coords: Vec<i32> = args[2..]
+        .iter()
+        .map(|a| a.parse().expect("coordinates must be integers"))
+        .collect();
+
+    let bytes = fs::read(&args[0]).expect("cannot read map");
+    let mut img = read_pgm(&bytes).unwrap_or_else(|e| panic!("invalid PGM: {}", e));
+    // The tree halves cells down to unit size, so the grid side must be a power of two
+    let n = (img.width.max(img.height) as u32).next_power_of_two() as i32;
+    let obstacles = obstacles(&img, n);
+    println!("Carte {}×{}: {} obstacles", img.width, img.height, obstacles.len());
+
+    // Pixel rows count down from the top, map rows up from the bottom
+    let flip = |x: i32, y: i32| (x, img.height as i32 - 1 - y);
+    let options = PlanOptions {
+        border_crossings: true,
+        reject_blocked_endpoints: true,
+        ..PlanOptions::default()
+    };
+    let planner = Planner::new(n, &obstacles, &options).expect("map has no free space");
+    let plan = planner
+        .query(flip(coords[0], coords[1]), flip(coords[2], coords[3]))
+        .unwrap_or_else(|e| panic!("no plan: {}", e));
+    println!("Distance {:.2} à travers {} régions", plan.cost, plan.path.len());
+
+    draw_path(&mut img, &plan.waypoints);
+    fs::write(&args[1], write_pgm(&img)).expect("cannot write output");
+    println!("Chemin écrit dans {}", args[1]);
+}
@@ -0,0 +1,47 @@
+//! Slides a wall across a 32×32 map, replanning after every move and writing one
+//! SVG frame per step: `cargo run --example replan [output_dir]`.
+
+use std::fs;
+use std::path::PathBuf;
+
+use veloquad::geometry::intersects;
+use veloquad::models::rect::Rect;
+use veloquad::planner::{PlanOptions, Planner};
+use veloquad::svg::render_svg;
+
+const STEPS: i32 = 24;
+
+fn main() {
+    let dir = PathBuf::from(std::env::args().nth(1).unwrap_or_else(|| "frames".to_string()));
+    fs::create_dir_all(&dir).expect("cannot create output directory");
+
+    let walls = vec![
+        Rect { x: 8, y: 0, w: 2, h: 20 },
+        Rect { x: 22, y: 12, w: 2, h: 20 },
+    ];
+    let mut planner = Planner::new(32, &walls, &PlanOptions::default()).unwrap();
+    planner.insert_obstacle(Rect { x: 2, y: 14, w: 4, h: 4 }).unwrap();
+
+    for step in 0..STEPS {
+        // The moving block is always the last obstacle
+        let last = planner.obstacles().len() - 1;
+        let block = planner.remove_obstacle(last).unwrap().unwrap();
+        let moved = Rect { x: 2 + step, ..block };
+        planner.insert_obstacle(moved).unwrap();
+
+        let plan = planner.query((1, 30), (30, 1)).expect("map stays connected");
+
+        // Every step must still produce a valid chain of adjacent free regions
+        for pair in plan.path.windows(2) {
+            assert!(planner.graph()[pair[0]].iter().any(|&(m, _)| m == pair[1]));
+        }
+        for &id in &plan.path {
+            let r = planner.region_rect(id);
+            assert!(!planner.obstacles().iter().any(|obs| intersects(obs, r.x, r.y, r.w)));
+        }
+
+        let frame = dir.join(format!("frame_{:03}.svg", step));
+        fs::write(&frame, render_svg(&planner, &[], Some(&plan.waypoints), 16.0)).unwrap();
+        println!("Étape {:>2}: distance {:.2}, {}", step, plan.cost, frame.display());
+    }
+}
@@ -1,10 +1,89 @@
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use crate::graph::find_nearest;
+use crate::models::quad::Quad;
+use crate::models::rect::Rect;
 use crate::models::state::State;
+use crate::quadtree::{leaf_at_point, leaf_neighbors};
+
+/// Outcome of a path search: total cost, visited node ids from start to goal,
+/// and the number of nodes popped from the heap.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchResult {
+    pub cost: f64,
+    pub path: Vec<usize>,
+    pub expansions: usize,
+}
 
 /// Runs Dijkstra's algorithm on the adjacency list `graph` from `start` to `goal`.
 /// Returns the shortest distance, or `None` if no path exists.
 pub fn dijkstra(graph: &[Vec<(usize, f64)>], start: usize, goal: usize) -> Option<f64> {
+    dijkstra_path(graph, start, goal).map(|result| result.cost)
+}
+
+/// Same as [`dijkstra`], but also reconstructs the node sequence of the shortest path.
+pub fn dijkstra_path(graph: &[Vec<(usize, f64)>], start: usize, goal: usize) -> Option<SearchResult> {
+    dijkstra_to_any(graph, start, &[goal])
+}
+
+/// Multi-target Dijkstra: stops at the first of `goals` to be settled, i.e. the
+/// cheapest one to reach. The path ends at that goal. `None` if no goal is reachable.
+pub fn dijkstra_to_any(graph: &[Vec<(usize, f64)>], start: usize, goals: &[usize]) -> Option<SearchResult> {
+    shortest_to_any(graph, start, goals, &HashSet::new(), 0.0, &|| false)
+}
+
+/// Same as [`dijkstra_path`], treating the nodes in `excluded` as unreachable: they
+/// are never entered, so no path runs through them. `start` may be excluded, the
+/// search still leaves from it; an excluded `goal` is never reached.
+pub fn dijkstra_excluding(
+    graph: &[Vec<(usize, f64)>],
+    start: usize,
+    goal: usize,
+    excluded: &HashSet<usize>,
+) -> Option<SearchResult> {
+    // Checked up front: a goal equal to `start` would be settled before any exclusion
+    if excluded.contains(&goal) {
+        return None;
+    }
+    shortest_to_any(graph, start, &[goal], excluded, 0.0, &|| false)
+}
+
+/// Same as [`dijkstra_path`], relaxing an edge only when it improves the best known
+/// distance by more than `epsilon`. Skips the churn of re-pushing nodes for rounding
+/// noise on float-heavy graphs. The cost returned is the true cost of the path
+/// returned, but that path may be up to `epsilon` per edge longer than the shortest;
+/// keep `epsilon` well below the smallest edge weight to stay near-optimal.
+pub fn dijkstra_epsilon(graph: &[Vec<(usize, f64)>], start: usize, goal: usize, epsilon: f64) -> Option<SearchResult> {
+    shortest_to_any(graph, start, &[goal], &HashSet::new(), epsilon, &|| false)
+}
+
+/// Same as [`dijkstra_epsilon`], polling `stop` before every expansion and giving up
+/// with `None` as soon as it returns true, e.g. when a deadline has passed.
+pub fn dijkstra_until(
+    graph: &[Vec<(usize, f64)>],
+    start: usize,
+    goal: usize,
+    epsilon: f64,
+    stop: &impl Fn() -> bool,
+) -> Option<SearchResult> {
+    shortest_to_any(graph, start, &[goal], &HashSet::new(), epsilon, stop)
+}
+
+fn shortest_to_any(
+    graph: &[Vec<(usize, f64)>],
+    start: usize,
+    goals: &[usize],
+    excluded: &HashSet<usize>,
+    epsilon: f64,
+    stop: &impl Fn() -> bool,
+) -> Option<SearchResult> {
+    let mut is_goal = vec![false; graph.len()];
+    for &goal in goals {
+        is_goal[goal] = true;
+    }
+
     let mut dist = vec![f64::INFINITY; graph.len()];
+    let mut prev = vec![usize::MAX; graph.len()];
     dist[start] = 0.0;
 
     let mut heap = BinaryHeap::new();
@@ -12,20 +91,537 @@ pub fn dijkstra(graph: &[Vec<(usize, f64)>], start: usize, goal: usize) -> Optio
         cost: 0.0,
         node: start,
     });
+    let mut expansions = 0;
 
     while let Some(State { cost, node }) = heap.pop() {
+        if stop() {
+            return None;
+        }
+        if is_goal[node] {
+            return Some(SearchResult {
+                cost,
+                path: reconstruct(&prev, start, node),
+                expansions,
+            });
+        }
+
+        if cost > dist[node] {
+            continue;
+        }
+        expansions += 1;
+
+        for &(neighbor, weight) in &graph[node] {
+            if excluded.contains(&neighbor) {
+                continue;
+            }
+            let next = cost + weight;
+            if next < dist[neighbor] - epsilon {
+                dist[neighbor] = next;
+                prev[neighbor] = node;
+                heap.push(State {
+                    cost: next,
+                    node: neighbor,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Bidirectional Dijkstra: searches forward from `start` and backward from `goal`
+/// at once, alternating on whichever frontier is closer, and stops once no path
+/// through the frontiers can beat the best meeting found. Same result as
+/// [`dijkstra_path`]; each search covers a disc of about half the radius, which
+/// pays off on open maps more than in winding corridors.
+///
+/// The backward search follows reversed edges, so directed weights, e.g. from a
+/// heading bias or [`set_edge_weight`](crate::graph::set_edge_weight), are honoured.
+pub fn bidirectional_dijkstra(graph: &[Vec<(usize, f64)>], start: usize, goal: usize) -> Option<SearchResult> {
+    let mut reverse = vec![vec![]; graph.len()];
+    for (from, edges) in graph.iter().enumerate() {
+        for &(to, weight) in edges {
+            reverse[to].push((from, weight));
+        }
+    }
+    let adjacency = [graph, &reverse[..]];
+    let mut dist = [vec![f64::INFINITY; graph.len()], vec![f64::INFINITY; graph.len()]];
+    let mut prev = [vec![usize::MAX; graph.len()], vec![usize::MAX; graph.len()]];
+    let mut heaps = [BinaryHeap::new(), BinaryHeap::new()];
+    for (side, node) in [start, goal].into_iter().enumerate() {
+        dist[side][node] = 0.0;
+        heaps[side].push(State {
+            cost: 0.0,
+            node,
+        });
+    }
+
+    let mut best = if start == goal { 0.0 } else { f64::INFINITY };
+    let mut meet = start;
+    let mut expansions = 0;
+
+    loop {
+        let top = |heap: &BinaryHeap<State>| heap.peek().map_or(f64::INFINITY, |s| s.cost);
+        let (f, b) = (top(&heaps[0]), top(&heaps[1]));
+        if f + b >= best {
+            break;
+        }
+        let side = if f <= b { 0 } else { 1 };
+        let Some(State { cost, node }) = heaps[side].pop() else {
+            break;
+        };
+        if cost > dist[side][node] {
+            continue;
+        }
+        expansions += 1;
+
+        for &(neighbor, weight) in &adjacency[side][node] {
+            let next = cost + weight;
+            if next < dist[side][neighbor] {
+                dist[side][neighbor] = next;
+                prev[side][neighbor] = node;
+                heaps[side].push(State {
+                    cost: next,
+                    node: neighbor,
+                });
+                let through = next + dist[1 - side][neighbor];
+                if through < best {
+                    best = through;
+                    meet = neighbor;
+                }
+            }
+        }
+    }
+
+    if !best.is_finite() {
+        return None;
+    }
+    let mut path = reconstruct(&prev[0], start, meet);
+    let mut node = meet;
+    while node != goal {
+        node = prev[1][node];
+        path.push(node);
+    }
+    Some(SearchResult {
+        cost: best,
+        path,
+        expansions,
+    })
+}
+
+/// Resource-constrained shortest path: the cheapest path from `start` to `goal`
+/// along which the summed `usage` stays within `cap`. `usage[node][k]` is what the
+/// edge `graph[node][k]` consumes, e.g. distance driven through mud.
+///
+/// Each node keeps the labels (cost, usage so far) not dominated by another one,
+/// since a costlier path that used less of the resource may still be the only one
+/// able to finish. Exact, but the label sets can grow on large graphs.
+pub fn resource_constrained_path(
+    graph: &[Vec<(usize, f64)>],
+    usage: &[Vec<f64>],
+    start: usize,
+    goal: usize,
+    cap: f64,
+) -> Option<SearchResult> {
+    // Arena of (node, cost, used, parent label); heap entries point into it
+    let mut labels = vec![(start, 0.0, 0.0, usize::MAX)];
+    let mut dead = vec![false];
+    let mut at: Vec<Vec<usize>> = vec![vec![]; graph.len()];
+    at[start].push(0);
+
+    let mut heap = BinaryHeap::new();
+    heap.push(State {
+        cost: 0.0,
+        node: 0,
+    });
+    let mut expansions = 0;
+
+    while let Some(State { node: label, .. }) = heap.pop() {
+        if dead[label] {
+            continue;
+        }
+        let (node, cost, used, _) = labels[label];
         if node == goal {
-            return Some(cost);
+            let mut path = vec![];
+            let mut l = label;
+            while l != usize::MAX {
+                path.push(labels[l].0);
+                l = labels[l].3;
+            }
+            path.reverse();
+            return Some(SearchResult {
+                cost,
+                path,
+                expansions,
+            });
+        }
+        expansions += 1;
+
+        for (&(neighbor, weight), &spent) in graph[node].iter().zip(&usage[node]) {
+            let (next, total) = (cost + weight, used + spent);
+            if total > cap || at[neighbor].iter().any(|&l| labels[l].1 <= next && labels[l].2 <= total) {
+                continue;
+            }
+            at[neighbor].retain(|&l| {
+                let dominated = next <= labels[l].1 && total <= labels[l].2;
+                if dominated {
+                    dead[l] = true;
+                }
+                !dominated
+            });
+            labels.push((neighbor, next, total, label));
+            dead.push(false);
+            at[neighbor].push(labels.len() - 1);
+            heap.push(State {
+                cost: next,
+                node: labels.len() - 1,
+            });
+        }
+    }
+
+    None
+}
+
+/// Earliest-arrival search on the time-expanded region graph, for obstacles that
+/// move on a known schedule. States are `(region, step)`: every step the path either
+/// crosses one edge or waits where it is, and `closed` lists `(region, from, until)`
+/// windows during whose steps `from..until` a region may not be occupied. Returns the
+/// path reaching `goal` at the earliest step within `horizon` steps, the cheapest by
+/// edge weight among those (waiting is free); `path[t]` is the region occupied at
+/// step `t`, so waits show up as repeated ids. `None` if the start is closed at step
+/// 0 or the goal cannot be reached in time.
+pub fn time_expanded_path(
+    graph: &[Vec<(usize, f64)>],
+    closed: &[(usize, u32, u32)],
+    start: usize,
+    goal: usize,
+    horizon: u32,
+) -> Option<SearchResult> {
+    let is_closed = |node: usize, t: u32| closed.iter().any(|&(r, from, until)| r == node && from <= t && t < until);
+    if is_closed(start, 0) {
+        return None;
+    }
+
+    // One layer per step: best cost to each region, and the predecessor layer's region
+    let mut layers: Vec<Vec<(f64, usize)>> = vec![vec![(f64::INFINITY, usize::MAX); graph.len()]];
+    layers[0][start].0 = 0.0;
+    let mut expansions = 0;
+
+    for t in 0..=horizon {
+        let layer = &layers[t as usize];
+        if layer[goal].0.is_finite() {
+            let mut path = vec![goal];
+            for step in (1..=t as usize).rev() {
+                path.push(layers[step][path[path.len() - 1]].1);
+            }
+            path.reverse();
+            return Some(SearchResult {
+                cost: layer[goal].0,
+                path,
+                expansions,
+            });
+        }
+        if t == horizon {
+            break;
+        }
+
+        let mut next = vec![(f64::INFINITY, usize::MAX); graph.len()];
+        for (node, &(cost, _)) in layer.iter().enumerate().filter(|(_, (c, _))| c.is_finite()) {
+            expansions += 1;
+            let moves = std::iter::once((node, 0.0)).chain(graph[node].iter().copied());
+            for (neighbor, weight) in moves {
+                if cost + weight < next[neighbor].0 && !is_closed(neighbor, t + 1) {
+                    next[neighbor] = (cost + weight, node);
+                }
+            }
         }
+        layers.push(next);
+    }
+
+    None
+}
 
+/// Dijkstra straight on the quadtree, for one-shot queries: neighbors are found
+/// with [`leaf_neighbors`] as each leaf is expanded, so only the leaves the search
+/// reaches are ever looked at and no region graph is built. Edges cost the distance
+/// between leaf centers, as in [`build_graph`](crate::graph::build_graph). Returns
+/// the cost and the leaves from the one containing `start` to the one containing
+/// `goal`, or `None` if either cell is blocked or they are not connected.
+pub fn dijkstra_lazy(quad: &Quad, start: (i32, i32), goal: (i32, i32)) -> Option<(f64, Vec<Rect>)> {
+    let from = leaf_at_point(quad, start.0, start.1)?;
+    let to = leaf_at_point(quad, goal.0, goal.1)?;
+    let center = |r: &Rect| (r.x as f64 + r.w as f64 / 2.0, r.y as f64 + r.h as f64 / 2.0);
+
+    // Leaves are numbered as they are discovered; leaves never share a corner
+    let mut ids: HashMap<(i32, i32), usize> = HashMap::from([((from.x, from.y), 0)]);
+    let mut leaves = vec![from];
+    let mut dist = vec![0.0];
+    let mut prev = vec![usize::MAX];
+
+    let mut heap = BinaryHeap::new();
+    heap.push(State {
+        cost: 0.0,
+        node: 0,
+    });
+
+    while let Some(State { cost, node }) = heap.pop() {
+        if leaves[node] == to {
+            let path = reconstruct(&prev, 0, node).into_iter().map(|i| leaves[i]).collect();
+            return Some((cost, path));
+        }
         if cost > dist[node] {
             continue;
         }
 
+        let (x1, y1) = center(&leaves[node]);
+        for r in leaf_neighbors(quad, &leaves[node]) {
+            let id = *ids.entry((r.x, r.y)).or_insert_with(|| {
+                leaves.push(r);
+                dist.push(f64::INFINITY);
+                prev.push(usize::MAX);
+                leaves.len() - 1
+            });
+            let (x2, y2) = center(&r);
+            let next = cost + ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+            if next < dist[id] {
+                dist[id] = next;
+                prev[id] = node;
+                heap.push(State {
+                    cost: next,
+                    node: id,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Greedy best-first search: always expands the open node whose center is closest to
+/// the goal's center, ignoring accumulated cost. Each node is expanded at most once.
+///
+/// This is fast on open maps but does **not** guarantee the shortest path; the
+/// returned `cost` is the true length of the path it happened to find.
+pub fn greedy_best_first(
+    graph: &[Vec<(usize, f64)>],
+    centers: &HashMap<usize, (f64, f64)>,
+    start: usize,
+    goal: usize,
+) -> Option<SearchResult> {
+    let (gx, gy) = centers[&goal];
+    let h = |node: usize| {
+        let (x, y) = centers[&node];
+        ((x - gx).powi(2) + (y - gy).powi(2)).sqrt()
+    };
+
+    let mut dist = vec![f64::INFINITY; graph.len()];
+    let mut prev = vec![usize::MAX; graph.len()];
+    let mut closed = vec![false; graph.len()];
+    dist[start] = 0.0;
+
+    let mut heap = BinaryHeap::new();
+    heap.push(State {
+        cost: h(start),
+        node: start,
+    });
+    let mut expansions = 0;
+
+    while let Some(State { node, .. }) = heap.pop() {
+        if node == goal {
+            return Some(SearchResult {
+                cost: dist[goal],
+                path: reconstruct(&prev, start, goal),
+                expansions,
+            });
+        }
+
+        if closed[node] {
+            continue;
+        }
+        closed[node] = true;
+        expansions += 1;
+
+        for &(neighbor, weight) in &graph[node] {
+            if closed[neighbor] || dist[neighbor].is_finite() {
+                continue;
+            }
+            dist[neighbor] = dist[node] + weight;
+            prev[neighbor] = node;
+            heap.push(State {
+                cost: h(neighbor),
+                node: neighbor,
+            });
+        }
+    }
+
+    None
+}
+
+/// A* from `start` to `goal`, guided by the anisotropic distance
+/// `sqrt((sx·dx)² + (sy·dy)²)` between region centers, where `scale = (sx, sy)`.
+///
+/// The result is optimal as long as no edge is cheaper than that distance between
+/// its endpoints' centers, which makes the heuristic consistent. Plain center-distance
+/// graphs satisfy this with `scale = (1.0, 1.0)`; graphs passed through
+/// [`apply_axis_scale`](crate::graph::apply_axis_scale) with `(ex, ey)` satisfy it for
+/// any `sx <= ex` and `sy <= ey`. A larger scale on either axis overestimates and may
+/// return a longer path, with fewer expansions.
+pub fn astar(
+    graph: &[Vec<(usize, f64)>],
+    centers: &HashMap<usize, (f64, f64)>,
+    start: usize,
+    goal: usize,
+    scale: (f64, f64),
+) -> Option<SearchResult> {
+    let (gx, gy) = centers[&goal];
+    let h = |node: usize| {
+        let (x, y) = centers[&node];
+        ((scale.0 * (x - gx)).powi(2) + (scale.1 * (y - gy)).powi(2)).sqrt()
+    };
+
+    let mut dist = vec![f64::INFINITY; graph.len()];
+    let mut prev = vec![usize::MAX; graph.len()];
+    dist[start] = 0.0;
+
+    let mut heap = BinaryHeap::new();
+    heap.push(State {
+        cost: h(start),
+        node: start,
+    });
+    let mut expansions = 0;
+
+    while let Some(State { cost, node }) = heap.pop() {
+        if node == goal {
+            return Some(SearchResult {
+                cost: dist[goal],
+                path: reconstruct(&prev, start, goal),
+                expansions,
+            });
+        }
+
+        if cost > dist[node] + h(node) {
+            continue;
+        }
+        expansions += 1;
+
+        for &(neighbor, weight) in &graph[node] {
+            let next = dist[node] + weight;
+            if next < dist[neighbor] {
+                dist[neighbor] = next;
+                prev[neighbor] = node;
+                heap.push(State {
+                    cost: next + h(neighbor),
+                    node: neighbor,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Maximin search on a graph whose weights are capacities, e.g. from
+/// [`border_width_graph`](crate::graph::border_width_graph): finds the path from
+/// `start` to `goal` whose narrowest edge is as wide as possible. Returns the path
+/// and that bottleneck width (infinite when `start == goal`), or `None` if `goal`
+/// is unreachable. Among paths of equal bottleneck, any may be returned.
+pub fn widest_path(graph: &[Vec<(usize, f64)>], start: usize, goal: usize) -> Option<(Vec<usize>, f64)> {
+    let mut width = vec![0.0; graph.len()];
+    let mut prev = vec![usize::MAX; graph.len()];
+    let mut done = vec![false; graph.len()];
+    width[start] = f64::INFINITY;
+
+    // The heap pops the lowest cost, so widths are pushed negated
+    let mut heap = BinaryHeap::new();
+    heap.push(State {
+        cost: -f64::INFINITY,
+        node: start,
+    });
+
+    while let Some(State { node, .. }) = heap.pop() {
+        if node == goal {
+            return Some((reconstruct(&prev, start, goal), width[goal]));
+        }
+        if done[node] {
+            continue;
+        }
+        done[node] = true;
+
+        for &(neighbor, capacity) in &graph[node] {
+            let through = width[node].min(capacity);
+            if !done[neighbor] && through > width[neighbor] {
+                width[neighbor] = through;
+                prev[neighbor] = node;
+                heap.push(State {
+                    cost: -through,
+                    node: neighbor,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Distances and predecessors of a Dijkstra run to completion from one start node.
+/// Answers any number of goal queries without searching again.
+#[derive(Clone, Debug)]
+pub struct SingleSourceResult {
+    pub start: usize,
+    /// Distance from `start` to every node, `f64::INFINITY` if unreachable.
+    pub dist: Vec<f64>,
+    prev: Vec<usize>,
+}
+
+impl SingleSourceResult {
+    /// Shortest distance from `start` to `goal`, or `None` if unreachable.
+    pub fn distance(&self, goal: usize) -> Option<f64> {
+        Some(self.dist[goal]).filter(|d| d.is_finite())
+    }
+
+    /// Shortest path from `start` to `goal`, rebuilt from the cached predecessors.
+    pub fn path_to(&self, goal: usize) -> Option<Vec<usize>> {
+        self.distance(goal)?;
+        Some(reconstruct(&self.prev, self.start, goal))
+    }
+}
+
+/// Runs Dijkstra from `start` until every reachable node is settled.
+pub fn dijkstra_single_source(graph: &[Vec<(usize, f64)>], start: usize) -> SingleSourceResult {
+    let (dist, prev) = settle_from(graph, &[start]);
+    SingleSourceResult { start, dist, prev }
+}
+
+/// Dijkstra seeded with every node of `sources` at cost 0: the distance from each
+/// node to its closest source (`f64::INFINITY` if none is reachable), e.g. a
+/// distance-to-nearest-exit map.
+pub fn dijkstra_multi_source(graph: &[Vec<(usize, f64)>], sources: &[usize]) -> Vec<f64> {
+    settle_from(graph, sources).0
+}
+
+/// Settles every node reachable from `sources`, returning distances and predecessors.
+fn settle_from(graph: &[Vec<(usize, f64)>], sources: &[usize]) -> (Vec<f64>, Vec<usize>) {
+    let mut dist = vec![f64::INFINITY; graph.len()];
+    let mut prev = vec![usize::MAX; graph.len()];
+
+    let mut heap = BinaryHeap::new();
+    for &source in sources {
+        dist[source] = 0.0;
+        heap.push(State {
+            cost: 0.0,
+            node: source,
+        });
+    }
+
+    while let Some(State { cost, node }) = heap.pop() {
+        if cost > dist[node] {
+            continue;
+        }
         for &(neighbor, weight) in &graph[node] {
             let next = cost + weight;
             if next < dist[neighbor] {
                 dist[neighbor] = next;
+                prev[neighbor] = node;
                 heap.push(State {
                     cost: next,
                     node: neighbor,
@@ -34,5 +630,230 @@ pub fn dijkstra(graph: &[Vec<(usize, f64)>], start: usize, goal: usize) -> Optio
         }
     }
 
-    None
+    (dist, prev)
+}
+
+/// Pairwise shortest distances between `points`, each snapped to its nearest region:
+/// `matrix[i][j]` is the graph distance from `points[i]` to `points[j]`,
+/// `f64::INFINITY` if unreachable. One full Dijkstra per distinct snapped region.
+pub fn all_pairs_among(
+    graph: &[Vec<(usize, f64)>],
+    centers: &HashMap<usize, (f64, f64)>,
+    points: &[(i32, i32)],
+) -> Vec<Vec<f64>> {
+    let nodes: Vec<usize> = points.iter().map(|&(x, y)| find_nearest(x, y, centers)).collect();
+    let mut runs: HashMap<usize, Vec<f64>> = HashMap::new();
+    nodes
+        .iter()
+        .map(|&from| {
+            let dist = runs
+                .entry(from)
+                .or_insert_with(|| dijkstra_single_source(graph, from).dist);
+            nodes.iter().map(|&to| dist[to]).collect()
+        })
+        .collect()
+}
+
+/// Runs Dijkstra from `start` to completion and returns the distance to every node
+/// (`f64::INFINITY` for unreachable ones).
+pub fn dijkstra_distances(graph: &[Vec<(usize, f64)>], start: usize) -> Vec<f64> {
+    dijkstra_single_source(graph, start).dist
+}
+
+/// Total area of the `regions` (indexed like `graph`) reachable from `start` at a
+/// cost of at most `budget`, e.g. what a robot can cover on one battery charge.
+/// The search never expands past the budget.
+pub fn reachable_area(graph: &[Vec<(usize, f64)>], regions: &[Rect], start: usize, budget: f64) -> u64 {
+    let mut dist = vec![f64::INFINITY; graph.len()];
+    dist[start] = 0.0;
+    let mut heap = BinaryHeap::new();
+    heap.push(State {
+        cost: 0.0,
+        node: start,
+    });
+    let mut area = 0;
+
+    while let Some(State { cost, node }) = heap.pop() {
+        if cost > dist[node] {
+            continue;
+        }
+        area += regions[node].w as u64 * regions[node].h as u64;
+
+        for &(neighbor, weight) in &graph[node] {
+            let next = cost + weight;
+            if next <= budget && next < dist[neighbor] {
+                dist[neighbor] = next;
+                heap.push(State {
+                    cost: next,
+                    node: neighbor,
+                });
+            }
+        }
+    }
+
+    area
+}
+
+/// Nearest-neighbour covering tour: starting at `start`, repeatedly moves to the
+/// closest unvisited node by graph distance until every node reachable from `start`
+/// has been visited. Returns the visiting order and the total travelled cost.
+/// A heuristic, not an optimal tour; each step costs one full Dijkstra.
+pub fn coverage_tour(graph: &[Vec<(usize, f64)>], start: usize) -> (Vec<usize>, f64) {
+    let mut visited = vec![false; graph.len()];
+    visited[start] = true;
+    let mut order = vec![start];
+    let mut total = 0.0;
+    let mut current = start;
+
+    loop {
+        let dist = dijkstra_distances(graph, current);
+        let next = (0..graph.len())
+            .filter(|&n| !visited[n] && dist[n].is_finite())
+            .min_by(|&a, &b| dist[a].partial_cmp(&dist[b]).unwrap());
+        let Some(next) = next else {
+            break;
+        };
+        visited[next] = true;
+        order.push(next);
+        total += dist[next];
+        current = next;
+    }
+
+    (order, total)
+}
+
+/// Walks the predecessor array back from `goal` and returns the path start → goal.
+pub(crate) fn reconstruct(prev: &[usize], start: usize, goal: usize) -> Vec<usize> {
+    let mut path = vec![goal];
+    let mut node = goal;
+    while node != start {
+        node = prev[node];
+        path.push(node);
+    }
+    path.reverse();
+    path
+}
+
+/// Finds up to two paths from `start` to `goal` that share no node other than the
+/// endpoints, minimising their total cost (Suurballe's problem, solved as a min-cost
+/// flow of 2 on the node-split graph). Returns both paths, cheapest first, a single
+/// path when no disjoint pair exists, or nothing when `goal` is unreachable.
+pub fn disjoint_paths(graph: &[Vec<(usize, f64)>], start: usize, goal: usize) -> Vec<(Vec<usize>, f64)> {
+    if start == goal {
+        return vec![(vec![start], 0.0)];
+    }
+
+    // Node v becomes 2v (in) -> 2v+1 (out); arc i and i^1 are residual twins
+    let mut to = Vec::new();
+    let mut cap = Vec::new();
+    let mut cost = Vec::new();
+    let mut arcs = vec![Vec::new(); 2 * graph.len()];
+    let mut add_arc = |from: usize, target: usize, c: i32, w: f64| {
+        arcs[from].push(to.len());
+        to.push(target);
+        cap.push(c);
+        cost.push(w);
+        arcs[target].push(to.len());
+        to.push(from);
+        cap.push(0);
+        cost.push(-w);
+    };
+    for (node, edges) in graph.iter().enumerate() {
+        let through = if node == start || node == goal { 2 } else { 1 };
+        add_arc(2 * node, 2 * node + 1, through, 0.0);
+        for &(neighbor, weight) in edges {
+            if weight.is_finite() && neighbor != node {
+                add_arc(2 * node + 1, 2 * neighbor, 1, weight);
+            }
+        }
+    }
+
+    let (source, sink) = (2 * start + 1, 2 * goal);
+    for _ in 0..2 {
+        // Residual costs can be negative, so use Bellman-Ford (queue based)
+        let mut dist = vec![f64::INFINITY; arcs.len()];
+        let mut via = vec![usize::MAX; arcs.len()];
+        let mut queued = vec![false; arcs.len()];
+        dist[source] = 0.0;
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            queued[node] = false;
+            for &a in &arcs[node] {
+                let next = dist[node] + cost[a];
+                if cap[a] > 0 && next < dist[to[a]] - 1e-12 {
+                    dist[to[a]] = next;
+                    via[to[a]] = a;
+                    if !queued[to[a]] {
+                        queued[to[a]] = true;
+                        queue.push_back(to[a]);
+                    }
+                }
+            }
+        }
+        if dist[sink].is_infinite() {
+            break;
+        }
+        let mut node = sink;
+        while node != source {
+            let a = via[node];
+            cap[a] -= 1;
+            cap[a ^ 1] += 1;
+            node = to[a ^ 1];
+        }
+    }
+
+    // Walk the saturated edge arcs out of `start` once per unit of flow. The
+    // decomposition should always be consistent, but a walk that dead-ends or
+    // revisits a node stops the decomposition with the paths found so far
+    let mut paths = Vec::new();
+    let mut visited = vec![false; graph.len()];
+    'walks: for &first in &arcs[source] {
+        if first % 2 != 0 || cap[first] != 0 {
+            continue;
+        }
+        let mut path = vec![start];
+        let mut total = cost[first];
+        let mut node = to[first] / 2;
+        while node != goal {
+            if node == start || visited[node] {
+                break 'walks;
+            }
+            visited[node] = true;
+            path.push(node);
+            let Some(a) = arcs[2 * node + 1].iter().copied().find(|&a| a % 2 == 0 && cap[a] == 0) else {
+                break 'walks;
+            };
+            total += cost[a];
+            node = to[a] / 2;
+        }
+        path.push(goal);
+        paths.push((path, total));
+    }
+    paths.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    paths
+}
+
+/// Breadth-first search returning every node reachable from `start` in at most `k` edges,
+/// ignoring edge weights. Nodes are listed in BFS order, starting with `start`.
+pub fn within_hops(graph: &[Vec<(usize, f64)>], start: usize, k: usize) -> Vec<usize> {
+    let mut hops = vec![usize::MAX; graph.len()];
+    hops[start] = 0;
+
+    let mut order = vec![start];
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        if hops[node] == k {
+            continue;
+        }
+        for &(neighbor, _) in &graph[node] {
+            if hops[neighbor] == usize::MAX {
+                hops[neighbor] = hops[node] + 1;
+                order.push(neighbor);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    order
 }
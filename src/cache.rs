@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+
+use crate::models::rect::Rect;
+use crate::models::terrain::Terrain;
+
+const HEADER: &str = "veloquad-graph 1";
+
+/// Errors raised while loading a cached graph.
+#[derive(Debug)]
+pub enum CacheError {
+    Io(io::Error),
+    /// The file is not a graph cache or is truncated.
+    Corrupt(String),
+    /// The cache was built from a different map.
+    Stale { expected: u64, found: u64 },
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Io(e) => write!(f, "io error: {}", e),
+            CacheError::Corrupt(message) => write!(f, "corrupt cache: {}", message),
+            CacheError::Stale { expected, found } => write!(
+                f,
+                "stale cache: built for map {:016x}, current map is {:016x}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CacheError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CacheError {
+    fn from(e: io::Error) -> Self {
+        CacheError::Io(e)
+    }
+}
+
+/// Region graph and geometry saved to disk, tagged with the checksum of the map
+/// it was built from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphCache {
+    pub checksum: u64,
+    pub regions: Vec<Rect>,
+    pub centers: HashMap<usize, (f64, f64)>,
+    pub graph: Vec<Vec<(usize, f64)>>,
+}
+
+/// FNV-1a hash of the grid size, obstacles and terrain, in input order.
+pub fn map_checksum(n: i32, obstacles: &[Rect], terrain: &[Terrain]) -> u64 {
+    let mut bytes = Vec::new();
+    bytes.extend(n.to_le_bytes());
+    bytes.extend((obstacles.len() as u64).to_le_bytes());
+    for r in obstacles {
+        for v in [r.x, r.y, r.w, r.h] {
+            bytes.extend(v.to_le_bytes());
+        }
+    }
+    bytes.extend((terrain.len() as u64).to_le_bytes());
+    for t in terrain {
+        for v in [t.rect.x, t.rect.y, t.rect.w, t.rect.h] {
+            bytes.extend(v.to_le_bytes());
+        }
+        bytes.extend(t.cost.to_le_bytes());
+    }
+    extend_checksum(0xcbf29ce484222325, &bytes)
+}
+
+/// Continues the FNV-1a hash `checksum` over `bytes`, e.g. to tag a map checksum with
+/// the settings a graph was built with.
+pub fn extend_checksum(checksum: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(checksum, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Writes the cache as text: a header, the checksum, one `x y w h cx cy` line per
+/// region, then one `degree (neighbor weight)*` line per region.
+pub fn save_graph(path: &str, cache: &GraphCache) -> io::Result<()> {
+    let mut out = format!("{}\n{:016x}\n{}\n", HEADER, cache.checksum, cache.regions.len());
+    for (id, r) in cache.regions.iter().enumerate() {
+        let (cx, cy) = cache.centers[&id];
+        out.push_str(&format!("{} {} {} {} {} {}\n", r.x, r.y, r.w, r.h, cx, cy));
+    }
+    for edges in &cache.graph {
+        out.push_str(&edges.len().to_string());
+        for (neighbor, weight) in edges {
+            out.push_str(&format!(" {} {}", neighbor, weight));
+        }
+        out.push('\n');
+    }
+    fs::write(path, out)
+}
+
+/// Reads a cache written by [`save_graph`] and rejects it unless its checksum
+/// matches `expected` (see [`map_checksum`]).
+pub fn load_graph(path: &str, expected: u64) -> Result<GraphCache, CacheError> {
+    let content = fs::read_to_string(path)?;
+    // Every region takes two lines, so the file bounds any count it claims
+    let limit = content.lines().count();
+    let mut lines = content.lines();
+    let mut next = |what: &str| {
+        lines
+            .next()
+            .ok_or_else(|| CacheError::Corrupt(format!("missing {}", what)))
+    };
+
+    if next("header")? != HEADER {
+        return Err(CacheError::Corrupt("unknown header".to_string()));
+    }
+    let found = u64::from_str_radix(next("checksum")?, 16)
+        .map_err(|_| CacheError::Corrupt("invalid checksum".to_string()))?;
+    if found != expected {
+        return Err(CacheError::Stale { expected, found });
+    }
+    let count: usize = next("region count")?
+        .parse()
+        .map_err(|_| CacheError::Corrupt("invalid region count".to_string()))?;
+
+    let mut regions = Vec::with_capacity(count.min(limit));
+    let mut centers = HashMap::with_capacity(count.min(limit));
+    for id in 0..count {
+        let fields: Vec<&str> = next("region")?.split_whitespace().collect();
+        let bad = || CacheError::Corrupt(format!("invalid region {}", id));
+        if fields.len() != 6 {
+            return Err(bad());
+        }
+        let ints = fields[..4]
+            .iter()
+            .map(|s| s.parse::<i32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| bad())?;
+        let cx: f64 = fields[4].parse().map_err(|_| bad())?;
+        let cy: f64 = fields[5].parse().map_err(|_| bad())?;
+        regions.push(Rect { x: ints[0], y: ints[1], w: ints[2], h: ints[3] });
+        centers.insert(id, (cx, cy));
+    }
+
+    let mut graph = Vec::with_capacity(count.min(limit));
+    for id in 0..count {
+        let fields: Vec<&str> = next("edge list")?.split_whitespace().collect();
+        let bad = || CacheError::Corrupt(format!("invalid edges for region {}", id));
+        let degree: usize = fields.first().ok_or_else(bad)?.parse().map_err(|_| bad())?;
+        if degree.checked_mul(2) != Some(fields.len() - 1) {
+            return Err(bad());
+        }
+        let mut edges = Vec::with_capacity(degree);
+        for pair in fields[1..].chunks(2) {
+            let neighbor: usize = pair[0].parse().map_err(|_| bad())?;
+            let weight: f64 = pair[1].parse().map_err(|_| bad())?;
+            if neighbor >= count {
+                return Err(bad());
+            }
+            edges.push((neighbor, weight));
+        }
+        graph.push(edges);
+    }
+
+    Ok(GraphCache {
+        checksum: found,
+        regions,
+        centers,
+        graph,
+    })
+}
@@ -0,0 +1,94 @@
+use std::collections::BinaryHeap;
+use crate::algo::{reconstruct, SearchResult};
+use crate::models::state::State;
+
+/// Compressed-sparse-row region graph: the neighbors of node `i` are
+/// `targets[offsets[i]..offsets[i + 1]]` with matching `weights`.
+/// Flat arrays keep a node's edges contiguous, which is friendlier to the cache
+/// than one heap allocation per adjacency list.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CsrGraph {
+    pub offsets: Vec<usize>,
+    pub targets: Vec<usize>,
+    pub weights: Vec<f64>,
+}
+
+impl CsrGraph {
+    /// Packs an adjacency list into CSR arrays, preserving edge order.
+    pub fn from_adjacency(graph: &[Vec<(usize, f64)>]) -> CsrGraph {
+        let edges: usize = graph.iter().map(Vec::len).sum();
+        let mut csr = CsrGraph {
+            offsets: Vec::with_capacity(graph.len() + 1),
+            targets: Vec::with_capacity(edges),
+            weights: Vec::with_capacity(edges),
+        };
+        csr.offsets.push(0);
+        for edges in graph {
+            for &(target, weight) in edges {
+                csr.targets.push(target);
+                csr.weights.push(weight);
+            }
+            csr.offsets.push(csr.targets.len());
+        }
+        csr
+    }
+
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Neighbor ids and edge weights of `node`.
+    pub fn neighbors(&self, node: usize) -> (&[usize], &[f64]) {
+        let range = self.offsets[node]..self.offsets[node + 1];
+        (&self.targets[range.clone()], &self.weights[range])
+    }
+}
+
+/// Dijkstra's algorithm over a [`CsrGraph`]; same results as `dijkstra_path`.
+pub fn dijkstra_csr(graph: &CsrGraph, start: usize, goal: usize) -> Option<SearchResult> {
+    let mut dist = vec![f64::INFINITY; graph.len()];
+    let mut prev = vec![usize::MAX; graph.len()];
+    dist[start] = 0.0;
+
+    let mut heap = BinaryHeap::new();
+    heap.push(State {
+        cost: 0.0,
+        node: start,
+    });
+    let mut expansions = 0;
+
+    while let Some(State { cost, node }) = heap.pop() {
+        if node == goal {
+            return Some(SearchResult {
+                cost,
+                path: reconstruct(&prev, start, goal),
+                expansions,
+            });
+        }
+
+        if cost > dist[node] {
+            continue;
+        }
+        expansions += 1;
+
+        let (targets, weights) = graph.neighbors(node);
+        for (&neighbor, &weight) in targets.iter().zip(weights) {
+            let next = cost + weight;
+            if next < dist[neighbor] {
+                dist[neighbor] = next;
+                prev[neighbor] = node;
+                heap.push(State {
+                    cost: next,
+                    node: neighbor,
+                });
+            }
+        }
+    }
+
+    None
+}
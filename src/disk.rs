@@ -0,0 +1,188 @@
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+
+use crate::algo::{reconstruct, SearchResult};
+use crate::graph::for_each_adjacent;
+use crate::models::quad::Quad;
+use crate::models::state::State;
+use crate::quadtree::{free_leaves, leaf_neighbors};
+
+const MAGIC: &[u8; 8] = b"VQCSR1\0\0";
+const HEADER_LEN: u64 = 24;
+const EDGE_LEN: u64 = 16;
+
+/// Writes the region graph of `quad` straight to a CSR file at `path` without ever
+/// holding the adjacency lists in memory. Only the edges are streamed: the free
+/// leaves, their ids and degrees, like `centers`, are kept in RAM, so memory is
+/// still O(V) in the number of regions.
+///
+/// Layout (little endian): magic, node count, edge count, `nodes + 1` offsets, then
+/// one `(target: u64, weight: f64)` pair per directed edge.
+pub fn write_disk_graph(path: &str, quad: &Quad, centers: &HashMap<usize, (f64, f64)>) -> io::Result<()> {
+    let leaves = free_leaves(quad);
+    let nodes = leaves.len();
+
+    // Pass 1: degrees, so that every offset is known before the first edge
+    let mut degree = vec![0u64; nodes];
+    for_each_adjacent(&leaves, |i, j| {
+        degree[i] += 1;
+        degree[j] += 1;
+    });
+    let mut offsets = Vec::with_capacity(nodes + 1);
+    offsets.push(0u64);
+    for d in &degree {
+        offsets.push(offsets[offsets.len() - 1] + d);
+    }
+
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(MAGIC)?;
+    out.write_all(&(nodes as u64).to_le_bytes())?;
+    out.write_all(&offsets[nodes].to_le_bytes())?;
+    for o in &offsets {
+        out.write_all(&o.to_le_bytes())?;
+    }
+
+    // Pass 2: each node's edges in node order, so records follow the offsets
+    let ids: HashMap<(i32, i32), usize> = leaves.iter().enumerate().map(|(i, r)| ((r.x, r.y), i)).collect();
+    for (i, leaf) in leaves.iter().enumerate() {
+        let (x1, y1) = centers[&i];
+        for other in leaf_neighbors(quad, leaf) {
+            let j = ids[&(other.x, other.y)];
+            let (x2, y2) = centers[&j];
+            let dist = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+            out.write_all(&(j as u64).to_le_bytes())?;
+            out.write_all(&dist.to_le_bytes())?;
+        }
+    }
+    out.flush()
+}
+
+/// Read-only handle on a graph written by [`write_disk_graph`]; neighbor lists are
+/// read from the file on demand.
+pub struct DiskGraph {
+    file: File,
+    nodes: usize,
+    edges_start: u64,
+    file_len: u64,
+}
+
+fn read_u64(file: &mut File) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    file.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn corrupt(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl DiskGraph {
+    pub fn open(path: &str) -> io::Result<DiskGraph> {
+        let mut file = OpenOptions::new().read(true).open(path)?;
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(corrupt("not a veloquad disk graph"));
+        }
+        let nodes = read_u64(&mut file)?;
+        let _edges = read_u64(&mut file)?;
+        let file_len = file.metadata()?.len();
+        let edges_start = nodes
+            .checked_add(1)
+            .and_then(|n| n.checked_mul(8))
+            .and_then(|n| n.checked_add(HEADER_LEN))
+            .filter(|&start| start <= file_len)
+            .ok_or_else(|| corrupt("offset table runs past the end of the file"))?;
+        Ok(DiskGraph {
+            file,
+            nodes: nodes as usize,
+            edges_start,
+            file_len,
+        })
+    }
+
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes == 0
+    }
+
+    /// Reads the neighbor ids and edge weights of `node` from disk. Fails with
+    /// [`io::ErrorKind::InvalidData`] if `node` is not a node of the graph or its
+    /// entries in the file are inconsistent.
+    pub fn neighbors(&mut self, node: usize) -> io::Result<Vec<(usize, f64)>> {
+        if node >= self.nodes {
+            return Err(corrupt("node out of range"));
+        }
+        self.file.seek(SeekFrom::Start(HEADER_LEN + 8 * node as u64))?;
+        let from = read_u64(&mut self.file)?;
+        let to = read_u64(&mut self.file)?;
+        let slot = |i: u64| i.checked_mul(EDGE_LEN).and_then(|b| b.checked_add(self.edges_start));
+        let (start, end) = match (slot(from), slot(to)) {
+            (Some(start), Some(end)) if from <= to && end <= self.file_len => (start, end),
+            _ => return Err(corrupt("edge offsets out of range")),
+        };
+
+        self.file.seek(SeekFrom::Start(start))?;
+        let mut buf = vec![0u8; (end - start) as usize];
+        self.file.read_exact(&mut buf)?;
+        buf.chunks_exact(EDGE_LEN as usize)
+            .map(|c| {
+                let target = u64::from_le_bytes(c[..8].try_into().unwrap());
+                let weight = f64::from_le_bytes(c[8..].try_into().unwrap());
+                if target >= self.nodes as u64 {
+                    return Err(corrupt("edge target out of range"));
+                }
+                Ok((target as usize, weight))
+            })
+            .collect()
+    }
+}
+
+/// Dijkstra's algorithm over a [`DiskGraph`], fetching each node's neighbors only
+/// when it is expanded. Memory use is the distance/predecessor arrays plus the heap.
+pub fn dijkstra_disk(graph: &mut DiskGraph, start: usize, goal: usize) -> io::Result<Option<SearchResult>> {
+    let mut dist = vec![f64::INFINITY; graph.len()];
+    let mut prev = vec![usize::MAX; graph.len()];
+    dist[start] = 0.0;
+
+    let mut heap = BinaryHeap::new();
+    heap.push(State {
+        cost: 0.0,
+        node: start,
+    });
+    let mut expansions = 0;
+
+    while let Some(State { cost, node }) = heap.pop() {
+        if node == goal {
+            return Ok(Some(SearchResult {
+                cost,
+                path: reconstruct(&prev, start, goal),
+                expansions,
+            }));
+        }
+
+        if cost > dist[node] {
+            continue;
+        }
+        expansions += 1;
+
+        for (neighbor, weight) in graph.neighbors(node)? {
+            let next = cost + weight;
+            if next < dist[neighbor] {
+                dist[neighbor] = next;
+                prev[neighbor] = node;
+                heap.push(State {
+                    cost: next,
+                    node: neighbor,
+                });
+            }
+        }
+    }
+
+    Ok(None)
+}
@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use crate::models::rect::Rect;
+use crate::models::shape::Shape;
 
 /// Returns true if the rectangle intersects the quadtree cell at (qx, qy) with side `qs`.
 pub fn intersects(rect: &Rect, qx: i32, qy: i32, qs: i32) -> bool {
@@ -15,3 +17,132 @@ pub fn covers(rect: &Rect, qx: i32, qy: i32, qs: i32) -> bool {
         && rect.y <= qy
         && rect.y + rect.h >= qy + qs
 }
+
+/// Composite version of [`intersects`]. May report a partial overlap that is
+/// actually carved out by a difference, which only costs an extra split; it never
+/// misses a real overlap.
+pub fn shape_intersects(shape: &Shape, qx: i32, qy: i32, qs: i32) -> bool {
+    match shape {
+        Shape::Rect(r) => intersects(r, qx, qy, qs),
+        Shape::Union(parts) => parts.iter().any(|p| shape_intersects(p, qx, qy, qs)),
+        Shape::Difference(a, b) => shape_intersects(a, qx, qy, qs) && !shape_covers(b, qx, qy, qs),
+    }
+}
+
+/// Composite version of [`covers`]. Conservative: a cell covered only jointly by
+/// several union members is reported as not covered and gets subdivided.
+pub fn shape_covers(shape: &Shape, qx: i32, qy: i32, qs: i32) -> bool {
+    match shape {
+        Shape::Rect(r) => covers(r, qx, qy, qs),
+        Shape::Union(parts) => parts.iter().any(|p| shape_covers(p, qx, qy, qs)),
+        Shape::Difference(a, b) => shape_covers(a, qx, qy, qs) && !shape_intersects(b, qx, qy, qs),
+    }
+}
+
+/// Returns true if the segment `a`–`b` passes through the interior of the rectangle.
+/// Segments that only touch the border or a corner do not count as intersecting.
+pub fn segment_intersects(rect: &Rect, a: (f64, f64), b: (f64, f64)) -> bool {
+    let (x0, y0) = (rect.x as f64, rect.y as f64);
+    let (x1, y1) = (x0 + rect.w as f64, y0 + rect.h as f64);
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+
+    // Liang–Barsky clipping of the segment against the closed rectangle
+    let mut t0: f64 = 0.0;
+    let mut t1: f64 = 1.0;
+    for (p, q) in [(-dx, a.0 - x0), (dx, x1 - a.0), (-dy, a.1 - y0), (dy, y1 - a.1)] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return false;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+    }
+    if t0 > t1 {
+        return false;
+    }
+
+    // The clipped part lies strictly inside only if its midpoint does
+    let t = (t0 + t1) / 2.0;
+    let (mx, my) = (a.0 + t * dx, a.1 + t * dy);
+    mx > x0 && mx < x1 && my > y0 && my < y1
+}
+
+/// Returns the segment shared by the borders of two non-overlapping rectangles,
+/// or `None` if they touch at a corner or not at all.
+pub fn shared_border(a: &Rect, b: &Rect) -> Option<((f64, f64), (f64, f64))> {
+    let vertical = if a.x + a.w == b.x {
+        Some(b.x)
+    } else if b.x + b.w == a.x {
+        Some(a.x)
+    } else {
+        None
+    };
+    if let Some(x) = vertical {
+        let lo = a.y.max(b.y);
+        let hi = (a.y + a.h).min(b.y + b.h);
+        if lo < hi {
+            return Some(((x as f64, lo as f64), (x as f64, hi as f64)));
+        }
+    }
+
+    let horizontal = if a.y + a.h == b.y {
+        Some(b.y)
+    } else if b.y + b.h == a.y {
+        Some(a.y)
+    } else {
+        None
+    };
+    if let Some(y) = horizontal {
+        let lo = a.x.max(b.x);
+        let hi = (a.x + a.w).min(b.x + b.w);
+        if lo < hi {
+            return Some(((lo as f64, y as f64), (hi as f64, y as f64)));
+        }
+    }
+
+    None
+}
+
+/// Euclidean distance between the closest points of two rectangles; 0 when they
+/// touch or overlap.
+pub fn rect_distance(a: &Rect, b: &Rect) -> f64 {
+    let gap = |lo1: i32, len1: i32, lo2: i32, len2: i32| (lo2 - (lo1 + len1)).max(lo1 - (lo2 + len2)).max(0);
+    let dx = gap(a.x, a.w, b.x, b.w) as f64;
+    let dy = gap(a.y, a.h, b.y, b.h) as f64;
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Convex hull of the region centers, counter-clockwise from the lowest, then
+/// leftmost, point (Andrew's monotone chain). Collinear points on the hull edges
+/// are dropped, so collinear centers yield only the two extremes and fewer than
+/// three distinct centers yield just those points.
+pub fn free_hull(centers: &HashMap<usize, (f64, f64)>) -> Vec<(f64, f64)> {
+    let mut points: Vec<(f64, f64)> = centers.values().copied().collect();
+    points.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then(a.0.partial_cmp(&b.0).unwrap()));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<(f64, f64)> = Vec::with_capacity(points.len() + 1);
+    // Right chain going up, then left chain coming back down
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let floor = hull.len();
+        for p in pass {
+            while hull.len() >= floor + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // The last point of each chain starts the next one
+        hull.pop();
+    }
+    hull
+}
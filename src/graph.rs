@@ -1,55 +1,512 @@
 use std::collections::HashMap;
-use crate::models::quad::Quad;
+use std::fmt;
+use crate::geometry::{segment_intersects, shared_border};
+use crate::models::quad::{Quad, QuadrantOrder};
+use crate::models::rect::Rect;
+use crate::quadtree::free_leaves_ordered;
 
-/// Recursively traverses the quadtree and builds an adjacency list graph.
-/// Sibling free cells within the same Split node are connected by Euclidean distance.
+/// Traverses the quadtree and builds an adjacency list graph, numbering free leaves
+/// from `*id` in the same order as `collect_free`.
+/// Free leaves that share a stretch of border are connected by the Euclidean
+/// distance between their centers.
 /// Returns the list of node ids belonging to the subtree rooted at `quad`.
 pub fn build_graph(
     quad: &Quad,
     id: &mut usize,
-    graph: &mut Vec<Vec<(usize, f64)>>,
+    graph: &mut [Vec<(usize, f64)>],
     centers: &HashMap<usize, (f64, f64)>,
 ) -> Vec<usize> {
-    match quad {
-        Quad::Free(_, _, _) => {
-            let current = *id;
-            *id += 1;
-            vec![current]
+    build_graph_ordered(quad, QuadrantOrder::default(), id, graph, centers)
+}
+
+/// Same as [`build_graph`], numbering leaves in `order` to match `collect_free_ordered`.
+pub fn build_graph_ordered(
+    quad: &Quad,
+    order: QuadrantOrder,
+    id: &mut usize,
+    graph: &mut [Vec<(usize, f64)>],
+    centers: &HashMap<usize, (f64, f64)>,
+) -> Vec<usize> {
+    let first = *id;
+    let leaves = free_leaves_ordered(quad, order);
+    *id += leaves.len();
+
+    connect_adjacent(first, &leaves, graph, centers);
+    (first..*id).collect()
+}
+
+/// Explicit-stack version of [`build_graph`]: assigns the same ids and adds the same
+/// edges without recursing, so very deep trees cannot overflow the call stack.
+pub fn build_graph_iterative(
+    quad: &Quad,
+    graph: &mut [Vec<(usize, f64)>],
+    centers: &HashMap<usize, (f64, f64)>,
+) -> Vec<usize> {
+    let mut leaves = Vec::new();
+    let mut stack = vec![quad];
+    while let Some(node) = stack.pop() {
+        match node {
+            Quad::Free(x, y, s) => leaves.push(Rect { x: *x, y: *y, w: *s, h: *s }),
+            // Reversed so children are numbered in NW, NE, SW, SE order
+            Quad::Split(children) => stack.extend(children.iter().rev()),
+            Quad::Blocked => {}
+        }
+    }
+
+    connect_adjacent(0, &leaves, graph, centers);
+    (0..leaves.len()).collect()
+}
+
+/// Adds an edge between every pair of leaves sharing a border, where `leaves[i]`
+/// has id `first + i`.
+fn connect_adjacent(
+    first: usize,
+    leaves: &[Rect],
+    graph: &mut [Vec<(usize, f64)>],
+    centers: &HashMap<usize, (f64, f64)>,
+) {
+    for_each_adjacent(leaves, |i, j| {
+        let id1 = first + i;
+        let id2 = first + j;
+        let (x1, y1) = centers[&id1];
+        let (x2, y2) = centers[&id2];
+        let dist = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+
+        graph[id1].push((id2, dist));
+        graph[id2].push((id1, dist));
+    });
+}
+
+/// Calls `f(i, j)` once for every pair of `leaves` sharing a border. Candidates are
+/// bucketed by border line so only leaves whose edges lie on the same line are
+/// compared.
+pub fn for_each_adjacent(leaves: &[Rect], mut f: impl FnMut(usize, usize)) {
+    let mut by_left: HashMap<i32, Vec<usize>> = HashMap::new();
+    let mut by_bottom: HashMap<i32, Vec<usize>> = HashMap::new();
+    for (i, r) in leaves.iter().enumerate() {
+        by_left.entry(r.x).or_default().push(i);
+        by_bottom.entry(r.y).or_default().push(i);
+    }
+
+    for (i, a) in leaves.iter().enumerate() {
+        let east = by_left.get(&(a.x + a.w)).into_iter().flatten();
+        let north = by_bottom.get(&(a.y + a.h)).into_iter().flatten();
+        for &j in east.chain(north) {
+            if shared_border(a, &leaves[j]).is_some() {
+                f(i, j);
+            }
+        }
+    }
+}
+
+/// Graph over `regions` indexed by position whose weights are the lengths of the
+/// borders shared by adjacent regions: how wide the passage between them is. The
+/// capacity input for [`widest_path`](crate::algo::widest_path).
+pub fn border_width_graph(regions: &[Rect]) -> Vec<Vec<(usize, f64)>> {
+    let mut graph = vec![vec![]; regions.len()];
+    for_each_adjacent(regions, |i, j| {
+        if let Some((p, q)) = shared_border(&regions[i], &regions[j]) {
+            let width = (q.0 - p.0) + (q.1 - p.1);
+            graph[i].push((j, width));
+            graph[j].push((i, width));
+        }
+    });
+    graph
+}
+
+/// Total length of the boundary of free space: the border between free leaves and
+/// blocked leaves, plus grid edges abutting free leaves. Each free leaf contributes
+/// its perimeter minus the stretches it shares with other free leaves.
+pub fn free_perimeter(quad: &Quad) -> f64 {
+    let leaves = free_leaves_ordered(quad, QuadrantOrder::default());
+    let mut total: f64 = leaves.iter().map(|r| 2.0 * (r.w + r.h) as f64).sum();
+    for_each_adjacent(&leaves, |i, j| {
+        if let Some((p, q)) = shared_border(&leaves[i], &leaves[j]) {
+            total -= 2.0 * ((q.0 - p.0) + (q.1 - p.1));
+        }
+    });
+    total
+}
+
+/// Distance between two points on a toroidal `n×n` grid: each axis takes the
+/// shorter way round.
+pub fn wrapped_distance(a: (f64, f64), b: (f64, f64), n: i32) -> f64 {
+    let n = n as f64;
+    let dx = (a.0 - b.0).abs();
+    let dy = (a.1 - b.1).abs();
+    (dx.min(n - dx).powi(2) + dy.min(n - dy).powi(2)).sqrt()
+}
+
+/// Adds the edges of a toroidal grid: leaves touching the west edge are linked to
+/// leaves touching the east edge when their y-ranges overlap, and likewise south to
+/// north. Weights use [`wrapped_distance`]. `regions[id]` is the rectangle of `id`.
+pub fn connect_wrapped(
+    n: i32,
+    regions: &[Rect],
+    graph: &mut [Vec<(usize, f64)>],
+    centers: &HashMap<usize, (f64, f64)>,
+) {
+    let west: Vec<usize> = (0..regions.len()).filter(|&i| regions[i].x == 0).collect();
+    let east: Vec<usize> = (0..regions.len()).filter(|&i| regions[i].x + regions[i].w == n).collect();
+    let south: Vec<usize> = (0..regions.len()).filter(|&i| regions[i].y == 0).collect();
+    let north: Vec<usize> = (0..regions.len()).filter(|&i| regions[i].y + regions[i].h == n).collect();
+
+    let overlap = |lo1: i32, len1: i32, lo2: i32, len2: i32| lo1.max(lo2) < (lo1 + len1).min(lo2 + len2);
+    let mut pairs = Vec::new();
+    for &a in &west {
+        for &b in &east {
+            let (ra, rb) = (&regions[a], &regions[b]);
+            if overlap(ra.y, ra.h, rb.y, rb.h) {
+                pairs.push((a, b));
+            }
+        }
+    }
+    for &a in &south {
+        for &b in &north {
+            let (ra, rb) = (&regions[a], &regions[b]);
+            if overlap(ra.x, ra.w, rb.x, rb.w) {
+                pairs.push((a, b));
+            }
+        }
+    }
+
+    for (a, b) in pairs {
+        if a == b || graph[a].iter().any(|&(m, _)| m == b) {
+            continue;
+        }
+        let dist = wrapped_distance(centers[&a], centers[&b], n);
+        graph[a].push((b, dist));
+        graph[b].push((a, dist));
+    }
+}
+
+/// Links every pair of regions whose centers are at most `radius` apart and can see
+/// each other, i.e. the segment between them crosses no obstacle interior. Sits
+/// between border adjacency and a full visibility graph; existing edges are kept.
+pub fn connect_visible(
+    radius: f64,
+    obstacles: &[Rect],
+    graph: &mut [Vec<(usize, f64)>],
+    centers: &HashMap<usize, (f64, f64)>,
+) {
+    // Sweep in x order so only centers within `radius` horizontally are compared
+    let mut ids: Vec<usize> = centers.keys().copied().collect();
+    ids.sort_by(|a, b| centers[a].0.partial_cmp(&centers[b].0).unwrap().then(a.cmp(b)));
+
+    for (i, &a) in ids.iter().enumerate() {
+        let (ax, ay) = centers[&a];
+        for &b in &ids[i + 1..] {
+            let (bx, by) = centers[&b];
+            if bx - ax > radius {
+                break;
+            }
+            let dist = ((bx - ax).powi(2) + (by - ay).powi(2)).sqrt();
+            if dist > radius || graph[a].iter().any(|&(m, _)| m == b) {
+                continue;
+            }
+            if obstacles.iter().any(|obs| segment_intersects(obs, (ax, ay), (bx, by))) {
+                continue;
+            }
+            graph[a].push((b, dist));
+            graph[b].push((a, dist));
+        }
+    }
+}
+
+/// Scales every edge by the mean cost multiplier of its two endpoints, roughly
+/// charging each half of the edge at the rate of the region it crosses.
+pub fn apply_costs(graph: &mut [Vec<(usize, f64)>], costs: &[f64]) {
+    for (node, edges) in graph.iter_mut().enumerate() {
+        for (neighbor, weight) in edges.iter_mut() {
+            *weight *= (costs[node] + costs[*neighbor]) / 2.0;
+        }
+    }
+}
+
+/// Multiplies every edge by `1 + alpha / s`, where `s` is the mean side length of the
+/// two regions it joins. Moves between large open cells stay close to their distance;
+/// moves between the fine cells that pack around obstacles cost more, which pushes
+/// paths away from clutter. `regions[id]` is the rectangle of `id`.
+pub fn apply_size_penalty(graph: &mut [Vec<(usize, f64)>], regions: &[Rect], alpha: f64) {
+    for (node, edges) in graph.iter_mut().enumerate() {
+        for (neighbor, weight) in edges.iter_mut() {
+            let mean = (regions[node].w + regions[*neighbor].w) as f64 / 2.0;
+            *weight *= 1.0 + alpha / mean;
         }
-        Quad::Split(children) => {
-            let mut ids = Vec::new();
-            for child in children.iter() {
-                ids.extend(build_graph(child, id, graph, centers));
+    }
+}
+
+/// Adds a directional penalty to every edge `u → v`: `weight × length × (1 − cos θ) / 2`,
+/// where θ is the angle between the edge and `heading`. Edges along the heading are
+/// unchanged, sideways ones pay half `weight`, backwards ones the full `weight` per
+/// unit length. The graph becomes asymmetric.
+pub fn apply_heading_bias(
+    graph: &mut [Vec<(usize, f64)>],
+    centers: &HashMap<usize, (f64, f64)>,
+    heading: (f64, f64),
+    weight: f64,
+) {
+    let norm = (heading.0.powi(2) + heading.1.powi(2)).sqrt();
+    if norm == 0.0 {
+        return;
+    }
+    let (hx, hy) = (heading.0 / norm, heading.1 / norm);
+
+    for (node, edges) in graph.iter_mut().enumerate() {
+        let (x1, y1) = centers[&node];
+        for (neighbor, cost) in edges.iter_mut() {
+            let (x2, y2) = centers[neighbor];
+            let (dx, dy) = (x2 - x1, y2 - y1);
+            let len = (dx.powi(2) + dy.powi(2)).sqrt();
+            if len > 0.0 {
+                let cos = (dx * hx + dy * hy) / len;
+                *cost += weight * len * (1.0 - cos) / 2.0;
             }
+        }
+    }
+}
 
-            // Connect every pair of free siblings
-            for i in 0..ids.len() {
-                for j in i + 1..ids.len() {
-                    let id1 = ids[i];
-                    let id2 = ids[j];
-                    let (x1, y1) = centers[&id1];
-                    let (x2, y2) = centers[&id2];
-                    let dist = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+/// Rescales every edge for anisotropic motion: a move of `(dx, dy)` between
+/// centers costs `sqrt((sx·dx)² + (sy·dy)²)` instead of its Euclidean length, with
+/// `scale = (sx, sy)`. Weights are multiplied by the ratio of the two, so factors
+/// applied before or after (terrain, size penalty) are kept. Pair with
+/// [`astar`](crate::algo::astar) using a scale no larger than `scale` on either axis.
+pub fn apply_axis_scale(graph: &mut [Vec<(usize, f64)>], centers: &HashMap<usize, (f64, f64)>, scale: (f64, f64)) {
+    for (from, edges) in graph.iter_mut().enumerate() {
+        let (x1, y1) = centers[&from];
+        for (to, weight) in edges.iter_mut() {
+            let (dx, dy) = (centers[to].0 - x1, centers[to].1 - y1);
+            let len = (dx * dx + dy * dy).sqrt();
+            if len > 0.0 {
+                *weight *= ((scale.0 * dx).powi(2) + (scale.1 * dy).powi(2)).sqrt() / len;
+            }
+        }
+    }
+}
 
-                    graph[id1].push((id2, dist));
-                    graph[id2].push((id1, dist));
-                }
+/// Caps every node at its `k` shortest edges, ties broken by neighbor id. An edge
+/// survives only if it is among the `k` shortest of both endpoints, so the graph
+/// stays undirected and no degree exceeds `k`. Dropping edges can make paths longer
+/// or disconnect regions that were connected; it trades optimality for speed on
+/// dense graphs such as those from [`connect_visible`].
+pub fn limit_degree(graph: &mut [Vec<(usize, f64)>], k: usize) {
+    let keep: Vec<Vec<usize>> = graph
+        .iter()
+        .map(|edges| {
+            let mut sorted = edges.clone();
+            sorted.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then(a.0.cmp(&b.0)));
+            sorted.into_iter().take(k).map(|(to, _)| to).collect()
+        })
+        .collect();
+    for (from, edges) in graph.iter_mut().enumerate() {
+        edges.retain(|&(to, _)| keep[from].contains(&to) && keep[to].contains(&from));
+    }
+}
+
+/// How [`apply_clearance_weighting`] trades path length against distance to obstacles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClearanceWeighting {
+    /// Prefer regions close to obstacles, e.g. for wall-following inspection:
+    /// edges cost `1 + weight * c` times more, `c` being the mean clearance of
+    /// their endpoints.
+    Hug(f64),
+    /// Prefer regions away from obstacles: edges cost `1 + weight / (1 + c)` times more.
+    Clear(f64),
+}
+
+/// Scales every edge by the clearance of the regions it joins, `clearance[i]` being
+/// the distance from region `i` to its nearest obstacle (see
+/// [`region_clearance`](crate::obstacles::region_clearance)). Paths still only use
+/// free regions; only which ones are preferred changes. Factors are at least 1, so
+/// center-distance heuristics stay admissible. Infinite clearance, on maps without
+/// obstacles, leaves weights unchanged.
+pub fn apply_clearance_weighting(graph: &mut [Vec<(usize, f64)>], clearance: &[f64], mode: ClearanceWeighting) {
+    for (from, edges) in graph.iter_mut().enumerate() {
+        for (to, weight) in edges.iter_mut() {
+            let c = (clearance[from] + clearance[*to]) / 2.0;
+            if !c.is_finite() {
+                continue;
             }
-            ids
+            *weight *= match mode {
+                ClearanceWeighting::Hug(w) => 1.0 + w * c,
+                ClearanceWeighting::Clear(w) => 1.0 + w / (1.0 + c),
+            };
+        }
+    }
+}
+
+/// Overrides the weight of the directed edge `from → to` only, leaving `to → from`
+/// untouched. An infinite weight makes the edge one-way. Returns false if the edge
+/// does not exist.
+pub fn set_edge_weight(graph: &mut [Vec<(usize, f64)>], from: usize, to: usize, weight: f64) -> bool {
+    match graph[from].iter_mut().find(|(neighbor, _)| *neighbor == to) {
+        Some(edge) => {
+            edge.1 = weight;
+            true
         }
-        Quad::Blocked => vec![],
+        None => false,
     }
 }
 
 /// Returns the id of the free cell whose center is nearest to the point (x, y).
+/// Equidistant centers resolve to the lowest id, so the result is stable across runs.
 pub fn find_nearest(x: i32, y: i32, centers: &HashMap<usize, (f64, f64)>) -> usize {
     centers
         .iter()
-        .min_by(|(_, (cx1, cy1)), (_, (cx2, cy2))| {
+        .min_by(|(id1, (cx1, cy1)), (id2, (cx2, cy2))| {
             let d1 = (x as f64 - cx1).powi(2) + (y as f64 - cy1).powi(2);
             let d2 = (x as f64 - cx2).powi(2) + (y as f64 - cy2).powi(2);
-            d1.partial_cmp(&d2).unwrap()
+            d1.partial_cmp(&d2).unwrap().then(id1.cmp(id2))
         })
         .map(|(id, _)| *id)
         .unwrap()
 }
+
+/// Ids of the `k` free cells whose centers are nearest to the point (x, y), closest
+/// first, with equidistant centers ordered by lowest id. Shorter than `k` only when
+/// there are fewer cells.
+pub fn find_k_nearest(x: i32, y: i32, centers: &HashMap<usize, (f64, f64)>, k: usize) -> Vec<usize> {
+    let mut by_dist: Vec<(f64, usize)> = centers
+        .iter()
+        .map(|(&id, (cx, cy))| ((x as f64 - cx).powi(2) + (y as f64 - cy).powi(2), id))
+        .collect();
+    by_dist.sort_by(|(d1, id1), (d2, id2)| d1.partial_cmp(d2).unwrap().then(id1.cmp(id2)));
+    by_dist.into_iter().take(k).map(|(_, id)| id).collect()
+}
+
+/// Area-weighted centroid of all free leaves of `quad`, and the id (as assigned by
+/// `collect_free`) of the free region whose center is nearest to it, the lowest
+/// id on ties. A sensible default start on maps without a fixed entry point. `None`
+/// if there is no free space.
+pub fn central_region(quad: &Quad) -> Option<(usize, (f64, f64))> {
+    let leaves = free_leaves_ordered(quad, QuadrantOrder::default());
+    let center = |r: &Rect| (r.x as f64 + r.w as f64 / 2.0, r.y as f64 + r.h as f64 / 2.0);
+
+    let area: f64 = leaves.iter().map(|r| r.w as f64 * r.h as f64).sum();
+    if area == 0.0 {
+        return None;
+    }
+    let (sx, sy) = leaves.iter().fold((0.0, 0.0), |(sx, sy), r| {
+        let (cx, cy) = center(r);
+        let a = r.w as f64 * r.h as f64;
+        (sx + a * cx, sy + a * cy)
+    });
+    let centroid = (sx / area, sy / area);
+
+    let dist = |r: &Rect| {
+        let (cx, cy) = center(r);
+        (cx - centroid.0).powi(2) + (cy - centroid.1).powi(2)
+    };
+    let nearest = (0..leaves.len())
+        .min_by(|&a, &b| dist(&leaves[a]).partial_cmp(&dist(&leaves[b])).unwrap().then(a.cmp(&b)))?;
+    Some((nearest, centroid))
+}
+
+/// Ids (as assigned by `collect_free`) of the free regions sharing a stretch of
+/// border with `obstacle`, in increasing order. Regions touching it only at a
+/// corner are left out.
+pub fn regions_bordering(obstacle: &Rect, quad: &Quad) -> Vec<usize> {
+    free_leaves_ordered(quad, QuadrantOrder::default())
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| shared_border(obstacle, r).is_some())
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// Nearest free region to the midpoint of each grid edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EdgeRegions {
+    pub north: usize,
+    pub south: usize,
+    pub east: usize,
+    pub west: usize,
+}
+
+/// Snaps the four edge midpoints of an `n×n` grid, `(n/2, n-1)`, `(n/2, 0)`,
+/// `(n-1, n/2)` and `(0, n/2)`, to their nearest free regions.
+pub fn edge_regions(n: i32, centers: &HashMap<usize, (f64, f64)>) -> EdgeRegions {
+    let mid = n / 2;
+    EdgeRegions {
+        north: find_nearest(mid, n - 1, centers),
+        south: find_nearest(mid, 0, centers),
+        east: find_nearest(n - 1, mid, centers),
+        west: find_nearest(0, mid, centers),
+    }
+}
+
+/// Snaps the point (x, y) to its nearest free cell, returning the cell id and the
+/// distance from the point to that cell's center.
+pub fn snap(x: i32, y: i32, centers: &HashMap<usize, (f64, f64)>) -> (usize, f64) {
+    let id = find_nearest(x, y, centers);
+    let (cx, cy) = centers[&id];
+    (id, ((x as f64 - cx).powi(2) + (y as f64 - cy).powi(2)).sqrt())
+}
+
+/// Largest graph [`to_adjacency_matrix`] accepts: 4096 nodes, a 128 MiB matrix.
+pub const MAX_MATRIX_NODES: usize = 4096;
+
+/// Returned by [`to_adjacency_matrix`] for graphs above [`MAX_MATRIX_NODES`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MatrixTooLarge {
+    pub nodes: usize,
+}
+
+impl fmt::Display for MatrixTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "graph of {} nodes exceeds the {} node matrix limit", self.nodes, MAX_MATRIX_NODES)
+    }
+}
+
+impl std::error::Error for MatrixTooLarge {}
+
+/// Dense view of the graph for matrix-based analysis: `m[i][j]` is the weight of the
+/// edge from `i` to `j`, the lightest one if there are several, `f64::INFINITY` when
+/// there is none and `0` on the diagonal.
+pub fn to_adjacency_matrix(graph: &[Vec<(usize, f64)>]) -> Result<Vec<Vec<f64>>, MatrixTooLarge> {
+    if graph.len() > MAX_MATRIX_NODES {
+        return Err(MatrixTooLarge { nodes: graph.len() });
+    }
+    let mut matrix = vec![vec![f64::INFINITY; graph.len()]; graph.len()];
+    for (from, edges) in graph.iter().enumerate() {
+        matrix[from][from] = 0.0;
+        for &(to, weight) in edges {
+            matrix[from][to] = matrix[from][to].min(weight);
+        }
+    }
+    Ok(matrix)
+}
+
+/// Projected size of the region graph of a tree, see [`estimate_graph_size`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphEstimate {
+    pub regions: usize,
+    /// Upper bound on undirected edges between adjacent regions.
+    pub edges: usize,
+    /// Rough heap bytes of the adjacency lists, centers and region rects, ignoring
+    /// allocator and hash table overhead.
+    pub bytes: usize,
+}
+
+/// Sizes the region graph of `quad` without building it, e.g. to reject a `min_size`
+/// that would produce too many regions. Counting free leaves is cheap; edges are
+/// bounded by planarity, at most `3V - 6` among `V` regions, or an average degree
+/// under 6. Edges added by `look_radius` or wrapping are not counted.
+pub fn estimate_graph_size(quad: &Quad) -> GraphEstimate {
+    fn count_free(quad: &Quad) -> usize {
+        match quad {
+            Quad::Free(..) => 1,
+            Quad::Blocked => 0,
+            Quad::Split(children) => children.iter().map(count_free).sum(),
+        }
+    }
+
+    let regions = count_free(quad);
+    let edges = if regions < 3 { regions.saturating_sub(1) } else { 3 * regions - 6 };
+    let per_region = size_of::<Vec<(usize, f64)>>() + size_of::<(usize, (f64, f64))>() + size_of::<Rect>();
+    GraphEstimate {
+        regions,
+        edges,
+        bytes: regions * per_region + 2 * edges * size_of::<(usize, f64)>(),
+    }
+}
@@ -0,0 +1,158 @@
+use crate::algo::dijkstra_path;
+use crate::geometry::{covers, intersects};
+use crate::graph::for_each_adjacent;
+use crate::models::rect::Rect;
+use crate::path::border_waypoints;
+use crate::planner::{NoPathReason, PlanError};
+use crate::quadtree::{build_quad, build_quad_coarse, free_leaves};
+
+/// Two-level planner for large maps: a coarse region graph, built with
+/// [`build_quad_coarse`], finds the long-range route, and only the coarse regions
+/// along it are decomposed at full resolution for the final search.
+pub struct HierarchicalPlanner {
+    n: i32,
+    obstacles: Vec<Rect>,
+    regions: Vec<Rect>,
+    /// Whether each coarse region is partly blocked and needs refining.
+    mixed: Vec<bool>,
+    graph: Vec<Vec<(usize, f64)>>,
+}
+
+/// A path found by [`HierarchicalPlanner::query`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HierarchicalResult {
+    pub cost: f64,
+    /// Fine regions from start to goal.
+    pub regions: Vec<Rect>,
+    pub waypoints: Vec<(f64, f64)>,
+    /// Coarse region ids of the initial long-range route.
+    pub coarse_path: Vec<usize>,
+    /// Number of coarse regions decomposed at full resolution.
+    pub refined: usize,
+}
+
+impl HierarchicalPlanner {
+    /// Builds the coarse graph of an `n×n` grid, never splitting cells below `min_size`.
+    /// Fails with [`PlanError::NoFreeSpace`] when no free cell remains.
+    pub fn new(n: i32, obstacles: &[Rect], min_size: i32) -> Result<HierarchicalPlanner, PlanError> {
+        let quad = build_quad_coarse(obstacles, 0, 0, n, min_size);
+        let regions = free_leaves(&quad);
+        if regions.is_empty() {
+            return Err(PlanError::NoFreeSpace);
+        }
+        let mixed = regions
+            .iter()
+            .map(|r| obstacles.iter().any(|obs| intersects(obs, r.x, r.y, r.w)))
+            .collect();
+        let graph = region_graph(&regions);
+
+        Ok(HierarchicalPlanner {
+            n,
+            obstacles: obstacles.to_vec(),
+            regions,
+            mixed,
+            graph,
+        })
+    }
+
+    /// Coarse regions, indexed by coarse id.
+    pub fn regions(&self) -> &[Rect] {
+        &self.regions
+    }
+
+    /// Routes on the coarse graph, then searches the full-resolution decomposition of
+    /// the coarse regions on that route. Coarse regions are optimistic, so if the
+    /// corridor turns out to be blocked it is widened by one ring of coarse neighbors
+    /// and searched again, until it covers the whole connected component.
+    /// The path is the shortest one inside the final corridor, which is not always
+    /// the shortest on the whole map.
+    pub fn query(&self, start: (i32, i32), goal: (i32, i32)) -> Result<HierarchicalResult, PlanError> {
+        self.check_bounds(start)?;
+        self.check_bounds(goal)?;
+        let blocked = |reason| PlanError::NoPath(reason);
+        let from = self.coarse_at(start).ok_or(blocked(NoPathReason::StartBlocked))?;
+        let to = self.coarse_at(goal).ok_or(blocked(NoPathReason::GoalBlocked))?;
+        let coarse = dijkstra_path(&self.graph, from, to).ok_or(blocked(NoPathReason::Disconnected))?;
+
+        let mut corridor = vec![false; self.regions.len()];
+        for &id in &coarse.path {
+            corridor[id] = true;
+        }
+        loop {
+            let leaves = self.refine(&corridor);
+            let at = |(x, y): (i32, i32)| leaves.iter().position(|r| covers(r, x, y, 1));
+            let a = at(start).ok_or(blocked(NoPathReason::StartBlocked))?;
+            let b = at(goal).ok_or(blocked(NoPathReason::GoalBlocked))?;
+            if let Some(found) = dijkstra_path(&region_graph(&leaves), a, b) {
+                return Ok(HierarchicalResult {
+                    cost: found.cost,
+                    waypoints: border_waypoints(&found.path, &leaves),
+                    regions: found.path.iter().map(|&i| leaves[i]).collect(),
+                    coarse_path: coarse.path,
+                    refined: corridor.iter().zip(&self.mixed).filter(|(c, m)| **c && **m).count(),
+                });
+            }
+            if !self.widen(&mut corridor) {
+                return Err(blocked(NoPathReason::Disconnected));
+            }
+        }
+    }
+
+    /// Fine leaves of the corridor: free coarse regions as they are, mixed ones
+    /// rebuilt at full resolution.
+    fn refine(&self, corridor: &[bool]) -> Vec<Rect> {
+        let mut leaves = Vec::new();
+        for (id, r) in self.regions.iter().enumerate().filter(|&(id, _)| corridor[id]) {
+            if self.mixed[id] {
+                leaves.extend(free_leaves(&build_quad(&self.obstacles, r.x, r.y, r.w)));
+            } else {
+                leaves.push(*r);
+            }
+        }
+        leaves
+    }
+
+    /// Adds every coarse neighbor of the corridor to it. Returns false if it was
+    /// already closed under adjacency.
+    fn widen(&self, corridor: &mut [bool]) -> bool {
+        let ring: Vec<usize> = (0..corridor.len())
+            .filter(|&i| corridor[i])
+            .flat_map(|i| self.graph[i].iter().map(|&(j, _)| j))
+            .filter(|&j| !corridor[j])
+            .collect();
+        for &j in &ring {
+            corridor[j] = true;
+        }
+        !ring.is_empty()
+    }
+
+    /// Coarse region containing the unit cell at `(x, y)`.
+    fn coarse_at(&self, (x, y): (i32, i32)) -> Option<usize> {
+        self.regions.iter().position(|r| covers(r, x, y, 1))
+    }
+
+    fn check_bounds(&self, (x, y): (i32, i32)) -> Result<(), PlanError> {
+        if x < 0 || y < 0 || x >= self.n || y >= self.n {
+            return Err(PlanError::InvalidBounds { x, y, n: self.n });
+        }
+        Ok(())
+    }
+}
+
+/// Graph over `leaves` indexed by position, connecting those that share a border by
+/// the distance between their centers.
+fn region_graph(leaves: &[Rect]) -> Vec<Vec<(usize, f64)>> {
+    let centers: Vec<(f64, f64)> = leaves
+        .iter()
+        .map(|r| (r.x as f64 + r.w as f64 / 2.0, r.y as f64 + r.h as f64 / 2.0))
+        .collect();
+    let mut graph = vec![vec![]; leaves.len()];
+    for_each_adjacent(leaves, |i, j| {
+        let ((x1, y1), (x2, y2)) = (centers[i], centers[j]);
+        let dist = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+        graph[i].push((j, dist));
+        graph[j].push((i, dist));
+    });
+    graph
+}
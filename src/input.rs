@@ -1,26 +1,265 @@
+use std::fmt;
 use std::fs;
+use std::io::{self, BufRead};
+use crate::models::map::Map;
 use crate::models::rect::Rect;
+use crate::models::terrain::Terrain;
+use crate::obstacles::snap_outward;
 
-pub fn read_input(path: &str) -> (i32, Vec<Rect>) {
-    let content = fs::read_to_string(path).unwrap();
+/// Errors raised while loading a map file.
+#[derive(Debug)]
+pub enum InputError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(e) => write!(f, "io error: {}", e),
+            InputError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for InputError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InputError::Io(e) => Some(e),
+            InputError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for InputError {
+    fn from(e: io::Error) -> Self {
+        InputError::Io(e)
+    }
+}
+
+fn parse_error(line: usize, message: impl Into<String>) -> InputError {
+    InputError::Parse {
+        line,
+        message: message.into(),
+    }
+}
+
+/// How the four numbers at the start of an obstacle line describe its rectangle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RectFormat {
+    /// `x y w h`: lower-left corner and size.
+    #[default]
+    Size,
+    /// `x1 y1 x2 y2`: two opposite corners, in either order.
+    Corners,
+}
+
+impl RectFormat {
+    /// Rewrites four parsed numbers as `x y w h`.
+    fn to_size<T: Copy + PartialOrd + std::ops::Sub<Output = T>>(self, v: [T; 4]) -> [T; 4] {
+        match self {
+            RectFormat::Size => v,
+            RectFormat::Corners => {
+                let span = |a: T, b: T| if a <= b { (a, b - a) } else { (b, a - b) };
+                let ((x, w), (y, h)) = (span(v[0], v[2]), span(v[1], v[3]));
+                [x, y, w, h]
+            }
+        }
+    }
+}
+
+/// Parses the single value on line `idx` (0-based) of the file.
+fn parse_line<T: std::str::FromStr>(lines: &[&str], idx: usize, what: &str) -> Result<T, InputError> {
+    parse_value(lines.get(idx).copied(), idx, what)
+}
+
+/// Parses `line`, line `idx` (0-based) of the file, as a single value.
+fn parse_value<T: std::str::FromStr>(line: Option<&str>, idx: usize, what: &str) -> Result<T, InputError> {
+    let line = line.ok_or_else(|| parse_error(idx + 1, format!("missing {}", what)))?;
+    line.trim()
+        .parse()
+        .map_err(|_| parse_error(idx + 1, format!("invalid {}: {:?}", what, line.trim())))
+}
+
+/// Parses an obstacle line, line `idx` (0-based) of the file, into its rectangle, cost
+/// and label.
+fn parse_obstacle(line: &str, idx: usize, format: RectFormat) -> Result<(Rect, f64, Option<String>), InputError> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if !(4..=6).contains(&fields.len()) {
+        return Err(parse_error(idx + 1, format!("expected 4 to 6 fields, got {}", fields.len())));
+    }
+    let invalid = || parse_error(idx + 1, format!("invalid obstacle: {:?}", line.trim()));
+    // Widened so corner differences cannot overflow
+    let ints = fields[..4].iter().map(|s| s.parse::<i64>()).collect::<Result<Vec<_>, _>>();
+    let rect = match ints {
+        Ok(nums) => {
+            let v = format.to_size([nums[0], nums[1], nums[2], nums[3]]).map(i32::try_from);
+            match v {
+                [Ok(x), Ok(y), Ok(w), Ok(h)] => Rect { x, y, w, h },
+                _ => return Err(invalid()),
+            }
+        }
+        // Fractional coordinates are rounded outward onto the grid
+        Err(_) => fields[..4]
+            .iter()
+            .map(|s| s.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+            .and_then(|v| {
+                let [x, y, w, h] = format.to_size([v[0], v[1], v[2], v[3]]);
+                snap_outward(x, y, w, h)
+            })
+            .ok_or_else(invalid)?,
+    };
+
+    // A numeric fifth field is the cost; anything else is the label
+    let mut rest = &fields[4..];
+    let cost = match rest.first() {
+        Some(field) if field.parse::<f64>().is_ok() => {
+            rest = &rest[1..];
+            field
+                .parse::<f64>()
+                .ok()
+                .filter(|c| c.is_finite() && *c >= 0.0)
+                .ok_or_else(|| parse_error(idx + 1, format!("invalid cost: {:?}", field)))?
+        }
+        _ => 0.0,
+    };
+    if rest.len() > 1 {
+        return Err(parse_error(idx + 1, format!("unexpected field: {:?}", rest[1])));
+    }
+    Ok((rect, cost, rest.first().map(|s| s.to_string())))
+}
+
+/// Reads a map file: grid size, obstacle count, then one `x y w h [cost] [label]` line per
+/// obstacle. A missing or zero `cost` makes the obstacle impassable; a positive one makes
+/// it penalty terrain whose traversal cost is multiplied by `cost`. Coordinates may be
+/// fractional; see [`snap_outward`] for how they are rounded. The optional
+/// non-numeric `label` (e.g. `wall`) is kept in [`Map`] for output styling.
+pub fn read_input(path: &str) -> Result<Map, InputError> {
+    read_input_with(path, RectFormat::Size)
+}
+
+/// Like [`read_input`], reading the first four numbers of each obstacle line as `format`.
+pub fn read_input_with(path: &str, format: RectFormat) -> Result<Map, InputError> {
+    let content = fs::read_to_string(path)?;
+    read_input_str_with(&content, format)
+}
+
+/// Parses map content already in memory; same format as [`read_input`].
+pub fn read_input_str(content: &str) -> Result<Map, InputError> {
+    read_input_str_with(content, RectFormat::Size)
+}
+
+/// Like [`read_input_str`], reading the first four numbers of each obstacle line as `format`.
+pub fn read_input_str_with(content: &str, format: RectFormat) -> Result<Map, InputError> {
     let lines: Vec<&str> = content.lines().collect();
 
-    let n: i32 = lines[0].trim().parse().unwrap();
-    let r: usize = lines[1].trim().parse().unwrap();
+    let n: i32 = parse_line(&lines, 0, "grid size")?;
+    if n <= 0 {
+        return Err(parse_error(1, format!("grid size must be positive, got {}", n)));
+    }
+    let r: usize = parse_line(&lines, 1, "obstacle count")?;
 
-    let mut obstacles = Vec::new();
+    let mut map = Map {
+        n,
+        ..Map::default()
+    };
     for i in 0..r {
-        let nums: Vec<i32> = lines[i + 2]
-            .split_whitespace()
-            .map(|s| s.parse().unwrap())
-            .collect();
-        obstacles.push(Rect {
-            x: nums[0],
-            y: nums[1],
-            w: nums[2],
-            h: nums[3],
-        });
-    }
-
-    (n, obstacles)
+        let idx = i + 2;
+        let line = lines
+            .get(idx)
+            .ok_or_else(|| parse_error(idx + 1, "missing obstacle"))?;
+        let (rect, cost, label) = parse_obstacle(line, idx, format)?;
+        if cost == 0.0 {
+            map.obstacles.push(rect);
+            map.obstacle_labels.push(label);
+        } else {
+            map.terrain.push(Terrain { rect, cost });
+            map.terrain_labels.push(label);
+        }
+    }
+
+    Ok(map)
+}
+
+/// Reads a map from `reader` one line at a time, for obstacle lists too large to hold
+/// in memory: the header is read up front, then the impassable obstacles are yielded
+/// in file order. Penalty-terrain lines are checked but skipped, and labels dropped;
+/// use [`read_input`] for maps that need them. After the first error the stream ends.
+pub struct ObstacleStream<R: BufRead> {
+    reader: R,
+    format: RectFormat,
+    n: i32,
+    remaining: usize,
+    /// 0-based index of the next line.
+    idx: usize,
+    line: String,
+}
+
+impl<R: BufRead> ObstacleStream<R> {
+    /// Reads the grid size and obstacle count of a [`read_input`] file.
+    pub fn new(reader: R) -> Result<ObstacleStream<R>, InputError> {
+        ObstacleStream::with_format(reader, RectFormat::Size)
+    }
+
+    /// Like [`ObstacleStream::new`], reading the first four numbers of each obstacle
+    /// line as `format`.
+    pub fn with_format(reader: R, format: RectFormat) -> Result<ObstacleStream<R>, InputError> {
+        let mut stream = ObstacleStream {
+            reader,
+            format,
+            n: 0,
+            remaining: 0,
+            idx: 0,
+            line: String::new(),
+        };
+        let n: i32 = parse_value(stream.next_line()?, 0, "grid size")?;
+        if n <= 0 {
+            return Err(parse_error(1, format!("grid size must be positive, got {}", n)));
+        }
+        stream.n = n;
+        stream.remaining = parse_value(stream.next_line()?, 1, "obstacle count")?;
+        Ok(stream)
+    }
+
+    /// Grid side.
+    pub fn n(&self) -> i32 {
+        self.n
+    }
+
+    /// Reads the next line into the buffer; `None` at end of input.
+    fn next_line(&mut self) -> Result<Option<&str>, InputError> {
+        self.line.clear();
+        self.idx += 1;
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(self.line.trim_end_matches(['\n', '\r'])))
+    }
+}
+
+impl<R: BufRead> Iterator for ObstacleStream<R> {
+    type Item = Result<Rect, InputError>;
+
+    fn next(&mut self) -> Option<Result<Rect, InputError>> {
+        while self.remaining > 0 {
+            self.remaining -= 1;
+            let (idx, format) = (self.idx, self.format);
+            let parsed = match self.next_line() {
+                Ok(Some(line)) => parse_obstacle(line, idx, format),
+                Ok(None) => Err(parse_error(idx + 1, "missing obstacle")),
+                Err(e) => Err(e),
+            };
+            match parsed {
+                Ok((rect, 0.0, _)) => return Some(Ok(rect)),
+                Ok(_) => {}
+                Err(e) => {
+                    self.remaining = 0;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
 }
@@ -0,0 +1,23 @@
+use std::collections::HashMap;
+
+use petgraph::graph::{DiGraph, NodeIndex};
+
+/// Converts the region graph into a `petgraph` graph: node `i` is region `i` with its
+/// center as weight, and every adjacency entry becomes a directed edge weighted by
+/// its cost, so asymmetric weights survive. Undirected algorithms can use
+/// `petgraph`'s own conversion, e.g. `graph.into_edge_type::<Undirected>()`.
+pub fn to_petgraph(
+    graph: &[Vec<(usize, f64)>],
+    centers: &HashMap<usize, (f64, f64)>,
+) -> DiGraph<(f64, f64), f64> {
+    let mut out = DiGraph::with_capacity(graph.len(), graph.iter().map(Vec::len).sum());
+    for id in 0..graph.len() {
+        out.add_node(centers[&id]);
+    }
+    for (from, edges) in graph.iter().enumerate() {
+        for &(to, weight) in edges {
+            out.add_edge(NodeIndex::new(from), NodeIndex::new(to), weight);
+        }
+    }
+    out
+}
@@ -1,6 +1,18 @@
 pub mod algo;
+pub mod cache;
+pub mod csr;
+pub mod disk;
 pub mod geometry;
 pub mod graph;
+pub mod hierarchy;
 pub mod input;
+#[cfg(feature = "petgraph")]
+pub mod interop;
 pub mod models;
-pub mod quadtree;
\ No newline at end of file
+pub mod obstacles;
+pub mod path;
+pub mod planner;
+pub mod quadtree;
+#[cfg(feature = "png")]
+pub mod raster;
+pub mod svg;
\ No newline at end of file
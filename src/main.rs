@@ -1,66 +1,292 @@
-mod algo;
-mod geometry;
-mod graph;
-mod input;
-mod models;
-mod quadtree;
-
-use std::collections::HashMap;
 use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
 
-use algo::dijkstra;
-use graph::{build_graph, find_nearest};
-use input::read_input;
-use quadtree::{build_quad, collect_free};
+use log::{Level, LevelFilter, Log, Metadata, Record};
 
-fn main() {
-    let mut args = env::args().skip(1); 
+use veloquad::cache::{load_graph, save_graph};
+use veloquad::input::{read_input_with, RectFormat};
+use veloquad::models::map::Map;
+use veloquad::planner::{graph_checksum, label_center, NoPathReason, PlanError, PlanOptions, Planner};
+#[cfg(feature = "png")]
+use veloquad::raster::write_png;
+use veloquad::svg::render_svg;
+
+const USAGE: &str = "\
+Usage: veloquad <commande> ...
+  build  <carte> <cache>                   construit le graphe et l'écrit dans <cache>
+  plan   <carte> [sx sy gx gy | départ arrivée] [-c cache] [--stats-json fichier]
+                                           cherche un chemin (défaut 0 0 7 7), les
+                                           extrémités pouvant être des étiquettes
+  render <carte> <sortie.svg|sortie.png|-> [sx sy gx gy]
+                                           image SVG, PNG (fonctionnalité `png`), ou
+                                           ASCII sur la sortie standard avec -
+  stats  <carte>                           métriques de l'arbre et du graphe
+Sans commande, `veloquad [-f] <carte> [sx sy gx gy]` équivaut à `plan`.
+Avec --corners, les obstacles sont lus comme deux coins `x1 y1 x2 y2`.
+-q n'affiche que les erreurs, -v ajoute les détails de construction et de recherche.";
+
+/// Status messages on stdout, warnings and errors on stderr.
+struct ConsoleLogger;
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
 
-    // Handle optional -f
-    let first = args.next().expect("Missing file path");
-    let file_path = if first == "-f" {
-        args.next().expect("Missing file path after -f")
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if record.level() <= Level::Warn {
+            eprintln!("{}", record.args());
+        } else {
+            println!("{}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: ConsoleLogger = ConsoleLogger;
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let level = if take_flag(&mut args, &["-q", "--quiet"]) {
+        LevelFilter::Error
+    } else if take_flag(&mut args, &["-v", "--verbose"]) {
+        LevelFilter::Debug
     } else {
-        first
+        LevelFilter::Info
+    };
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+    let format = if take_flag(&mut args, &["--corners"]) {
+        RectFormat::Corners
+    } else {
+        RectFormat::Size
+    };
+    let Some(command) = args.first() else {
+        usage();
     };
 
-    // Default coordinates
-    let mut sx = 0;
-    let mut sy = 0;
-    let mut gx = 7;
-    let mut gy = 7;
-
-    // If 4 extra args exist, override defaults
-    if let (Some(a), Some(b), Some(c), Some(d)) =
-        (args.next(), args.next(), args.next(), args.next())
-    {
-        sx = a.parse().expect("Invalid start x");
-        sy = b.parse().expect("Invalid start y");
-        gx = c.parse().expect("Invalid goal x");
-        gy = d.parse().expect("Invalid goal y");
+    match command.as_str() {
+        "build" => build(&args[1..], format),
+        "plan" => plan(&args[1..], format),
+        "render" => render(&args[1..], format),
+        "stats" => stats(&args[1..], format),
+        "-h" | "--help" => {
+            println!("{}", USAGE);
+        }
+        // Historical form: optional -f, map, then start and goal
+        "-f" => plan(&args[1..], format),
+        _ => plan(&args, format),
     }
+}
 
-    let (n, obstacles) = read_input(&file_path);
+fn build(args: &[String], format: RectFormat) {
+    let [map_path, cache_path] = args else {
+        usage();
+    };
+    let (map, planner) = load(map_path, format);
+    save_graph(cache_path, &planner.to_cache()).unwrap_or_else(|e| fail(PlanError::Io(e)));
+    log::info!(
+        "Graphe écrit: {} ({} régions, {} arêtes, carte {:016x})",
+        cache_path,
+        planner.centers().len(),
+        planner.stats().edges,
+        graph_checksum(map.n, &map.obstacles, &map.terrain, &PlanOptions::default())
+    );
+}
 
-    println!("Grille: {}x{}, Obstacles: {}", n, n, obstacles.len());
+fn plan(args: &[String], format: RectFormat) {
+    let mut rest: Vec<&String> = args.iter().collect();
+    let cache = take_option(&mut rest, "-c");
+    let stats_json = take_option(&mut rest, "--stats-json");
+    let Some(map_path) = rest.first() else {
+        usage();
+    };
+    let map = read_map(map_path, format);
+    let (start, goal) = endpoints(&rest[1..], &map);
+    let planner = match cache {
+        Some(path) => {
+            let checksum = graph_checksum(map.n, &map.obstacles, &map.terrain, &PlanOptions::default());
+            let cached = load_graph(&path, checksum).unwrap_or_else(|e| {
+                log::error!("Erreur: {}", e);
+                process::exit(1);
+            });
+            Planner::from_cache(map.n, &map.obstacles, &map.terrain, cached, &PlanOptions::default())
+                .unwrap_or_else(|e| {
+                    log::error!("Erreur: {}", e);
+                    process::exit(1);
+                })
+        }
+        None => build_planner(&map),
+    };
+    log::info!("Régions libres: {}", planner.centers().len());
 
-    let quad = build_quad(&obstacles, 0, 0, n);
+    let result = planner.query(start, goal);
+    if let Some(path) = stats_json {
+        let stats = result.as_ref().map_or(planner.stats(), |r| &r.stats);
+        fs::write(&path, stats.to_json() + "\n").unwrap_or_else(|e| fail(PlanError::Io(e)));
+    }
 
-    let mut centers: HashMap<usize, (f64, f64)> = HashMap::new();
-    let mut id = 0;
-    collect_free(&quad, &mut id, &mut centers);
+    match result {
+        Ok(result) => println!("Distance trouvée: {:.2}", result.cost),
+        Err(PlanError::NoPath(reason)) => match reason {
+            NoPathReason::StartBlocked => println!("Pas de chemin! (départ dans un obstacle)"),
+            NoPathReason::GoalBlocked => println!("Pas de chemin! (arrivée dans un obstacle)"),
+            NoPathReason::Disconnected => println!("Pas de chemin! (zones non connectées)"),
+            NoPathReason::OutsideArea => println!("Pas de chemin! (hors de la zone autorisée)"),
+        },
+        Err(e) => fail(e),
+    }
+}
 
-    println!("Régions libres: {}", centers.len());
+fn render(args: &[String], format: RectFormat) {
+    let (Some(map_path), Some(out)) = (args.first(), args.get(1)) else {
+        usage();
+    };
+    let (map, planner) = load(map_path, format);
+    let (start, goal) = endpoints(&args.iter().skip(2).collect::<Vec<_>>(), &map);
+    let plan = planner.query(start, goal).ok();
 
-    let mut graph = vec![vec![]; centers.len()];
-    let mut id = 0;
-    build_graph(&quad, &mut id, &mut graph, &centers);
+    if out == "-" {
+        print!("{}", ascii(&planner, plan.as_ref().map(|p| p.path.as_slice())));
+        return;
+    }
+    let waypoints = plan.as_ref().map(|p| p.waypoints.as_slice());
+    let px = (512 / planner.size()).max(1);
+    match Path::new(out).extension().and_then(|e| e.to_str()) {
+        Some("svg") => {
+            let svg = render_svg(&planner, &map.obstacle_labels, waypoints, px as f64);
+            fs::write(out, svg).unwrap_or_else(|e| fail(PlanError::Io(e)));
+        }
+        #[cfg(feature = "png")]
+        Some("png") => write_png(out, &planner, waypoints, px as u32).unwrap_or_else(|e| fail(PlanError::Io(e))),
+        #[cfg(not(feature = "png"))]
+        Some("png") => {
+            log::error!("Erreur: la sortie PNG demande la fonctionnalité `png` (cargo build --features png)");
+            process::exit(1);
+        }
+        _ => {
+            log::error!("Erreur: format de sortie non pris en charge: {} (.svg, .png ou -)", out);
+            process::exit(1);
+        }
+    }
+    log::info!("Image écrite: {}", out);
+}
+
+fn stats(args: &[String], format: RectFormat) {
+    let [map_path] = args else {
+        usage();
+    };
+    let (_, planner) = load(map_path, format);
+    let stats = planner.stats();
+    let largest = planner.regions().iter().map(|r| r.w).max().unwrap_or(0);
+    let smallest = planner.regions().iter().map(|r| r.w).min().unwrap_or(0);
+
+    println!("Régions libres: {}", stats.regions);
+    println!("Arêtes: {}", stats.edges);
+    println!("Taille des cellules: {} à {}", smallest, largest);
+    println!("Construction de l'arbre: {:?}", stats.build_time);
+    println!("Construction du graphe: {:?}", stats.graph_time);
+}
+
+/// Text view of the grid, top row first: `#` blocked, `.` free, `*` on the path.
+fn ascii(planner: &Planner, path: Option<&[usize]>) -> String {
+    let n = planner.size() as usize;
+    let mut grid = vec![vec!['#'; n]; n];
+    for r in planner.regions() {
+        for y in r.y..r.y + r.h {
+            for x in r.x..r.x + r.w {
+                grid[y as usize][x as usize] = '.';
+            }
+        }
+    }
+    for &id in path.unwrap_or(&[]) {
+        let r = planner.region_rect(id);
+        let (cx, cy) = (r.x + r.w / 2, r.y + r.h / 2);
+        grid[cy as usize][cx as usize] = '*';
+    }
+
+    let mut out = String::new();
+    for row in grid.iter().rev() {
+        out.extend(row);
+        out.push('\n');
+    }
+    out
+}
+
+/// Removes every occurrence of `flags` from `args`, returning true if there was one.
+fn take_flag(args: &mut Vec<String>, flags: &[&str]) -> bool {
+    let before = args.len();
+    args.retain(|a| !flags.contains(&a.as_str()));
+    args.len() != before
+}
 
-    let start = find_nearest(sx, sy, &centers);
-    let goal = find_nearest(gx, gy, &centers);
+/// Removes `flag` and its value from `args`, returning the value if present.
+fn take_option(args: &mut Vec<&String>, flag: &str) -> Option<String> {
+    let i = args.iter().position(|a| *a == flag)?;
+    if i + 1 >= args.len() {
+        usage();
+    }
+    let value = args[i + 1].clone();
+    args.drain(i..=i + 1);
+    Some(value)
+}
+
+/// Start and goal from four optional coordinates or two region labels,
+/// (0, 0) → (7, 7) by default.
+fn endpoints(args: &[&String], map: &Map) -> ((i32, i32), (i32, i32)) {
+    match args {
+        [] => ((0, 0), (7, 7)),
+        [from, to] => {
+            let resolve = |label: &String| label_center(map, label).unwrap_or_else(|e| fail(e));
+            (resolve(from), resolve(to))
+        }
+        [a, b, c, d] => {
+            let parse = |s: &String, what: &str| {
+                s.parse().unwrap_or_else(|_| {
+                    log::error!("Erreur: {} invalide: {:?}", what, s);
+                    process::exit(1);
+                })
+            };
+            (
+                (parse(a, "départ x"), parse(b, "départ y")),
+                (parse(c, "arrivée x"), parse(d, "arrivée y")),
+            )
+        }
+        _ => usage(),
+    }
+}
 
-    match dijkstra(&graph, start, goal) {
-        Some(dist) => println!("Distance trouvée: {:.2}", dist),
-        None => println!("Pas de chemin!"),
+fn read_map(path: &str, format: RectFormat) -> Map {
+    let map = read_input_with(path, format).unwrap_or_else(|e| fail(e.into()));
+    log::info!("Grille: {}x{}, Obstacles: {}", map.n, map.n, map.obstacles.len());
+    if !map.terrain.is_empty() {
+        log::info!("Terrains pénalisants: {}", map.terrain.len());
     }
-}
\ No newline at end of file
+    map
+}
+
+fn build_planner(map: &Map) -> Planner {
+    Planner::with_terrain(map.n, &map.obstacles, &map.terrain, &PlanOptions::default()).unwrap_or_else(|e| fail(e))
+}
+
+fn load(path: &str, format: RectFormat) -> (Map, Planner) {
+    let map = read_map(path, format);
+    let planner = build_planner(&map);
+    (map, planner)
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+fn fail(e: PlanError) -> ! {
+    log::error!("Erreur: {}", e);
+    process::exit(1);
+}
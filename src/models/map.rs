@@ -0,0 +1,15 @@
+use crate::models::rect::Rect;
+use crate::models::terrain::Terrain;
+
+/// Parsed map file: grid side, impassable obstacles and penalty terrain.
+/// `obstacle_labels[i]` and `terrain_labels[i]` hold the optional label of the
+/// matching entry; labels never affect planning, but can name query endpoints
+/// (see [`label_center`](crate::planner::label_center)).
+#[derive(Clone, Debug, Default)]
+pub struct Map {
+    pub n: i32,
+    pub obstacles: Vec<Rect>,
+    pub terrain: Vec<Terrain>,
+    pub obstacle_labels: Vec<Option<String>>,
+    pub terrain_labels: Vec<Option<String>>,
+}
@@ -1,3 +1,6 @@
+pub mod map;
 pub mod quad;
 pub mod rect;
+pub mod shape;
 pub mod state;
+pub mod terrain;
@@ -1,6 +1,33 @@
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Quad {
     Free(i32, i32, i32), // x, y, size
     Blocked,
     Split(Box<[Quad; 4]>),
 }
+
+/// Order in which the children of a `Split` are visited when numbering free leaves.
+/// Children are always stored as `[NW, NE, SW, SE]`; only traversal changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuadrantOrder {
+    /// Top row first, west to east.
+    #[default]
+    NwNeSwSe,
+    /// Bottom row first, west to east (Z-order with y pointing up).
+    SwSeNwNe,
+    /// West column first, top to bottom.
+    NwSwNeSe,
+    /// Clockwise from the top-left.
+    NwNeSeSw,
+}
+
+impl QuadrantOrder {
+    /// Indices into the `[NW, NE, SW, SE]` child array, in visiting order.
+    pub fn indices(self) -> [usize; 4] {
+        match self {
+            QuadrantOrder::NwNeSwSe => [0, 1, 2, 3],
+            QuadrantOrder::SwSeNwNe => [2, 3, 0, 1],
+            QuadrantOrder::NwSwNeSe => [0, 2, 1, 3],
+            QuadrantOrder::NwNeSeSw => [0, 1, 3, 2],
+        }
+    }
+}
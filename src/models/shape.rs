@@ -0,0 +1,17 @@
+use crate::models::rect::Rect;
+
+/// Obstacle built from rectangles by union and difference.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Rect(Rect),
+    Union(Vec<Shape>),
+    /// Area of the first shape not covered by the second.
+    Difference(Box<Shape>, Box<Shape>),
+}
+
+impl Shape {
+    /// Ring-shaped wall: `outer` with the `inner` rectangle left free.
+    pub fn hollow(outer: Rect, inner: Rect) -> Shape {
+        Shape::Difference(Box::new(Shape::Rect(outer)), Box::new(Shape::Rect(inner)))
+    }
+}
@@ -0,0 +1,8 @@
+use crate::models::rect::Rect;
+
+/// Traversable area whose crossing cost is multiplied by `cost` (> 0).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Terrain {
+    pub rect: Rect,
+    pub cost: f64,
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use crate::geometry::rect_distance;
+use crate::models::rect::Rect;
+
+/// Rewrites `obstacles` as a set of disjoint rectangles covering exactly the same
+/// cells, merging adjacent and overlapping pieces into larger blocks.
+///
+/// Edges are compressed onto the distinct x and y coordinates, then rectangles are
+/// grown greedily from the bottom-left: as far right as the row stays covered, then as
+/// far up as the whole span stays covered. The result is not guaranteed minimal but
+/// collapses grids of unit obstacles into a handful of blocks. Empty rectangles
+/// (`w <= 0` or `h <= 0`) are dropped.
+pub fn merge_obstacles(obstacles: &[Rect]) -> Vec<Rect> {
+    let rects: Vec<&Rect> = obstacles.iter().filter(|r| r.w > 0 && r.h > 0).collect();
+    if rects.is_empty() {
+        return Vec::new();
+    }
+
+    let mut xs: Vec<i32> = rects.iter().flat_map(|r| [r.x, r.x + r.w]).collect();
+    let mut ys: Vec<i32> = rects.iter().flat_map(|r| [r.y, r.y + r.h]).collect();
+    xs.sort_unstable();
+    xs.dedup();
+    ys.sort_unstable();
+    ys.dedup();
+
+    // covered[j][i] is the compressed cell [xs[i], xs[i+1]) x [ys[j], ys[j+1])
+    let (cols, rows) = (xs.len() - 1, ys.len() - 1);
+    let mut covered = vec![vec![false; cols]; rows];
+    for r in &rects {
+        let i0 = xs.binary_search(&r.x).unwrap();
+        let i1 = xs.binary_search(&(r.x + r.w)).unwrap();
+        let j0 = ys.binary_search(&r.y).unwrap();
+        let j1 = ys.binary_search(&(r.y + r.h)).unwrap();
+        for row in &mut covered[j0..j1] {
+            for cell in &mut row[i0..i1] {
+                *cell = true;
+            }
+        }
+    }
+
+    let mut merged = Vec::new();
+    for j in 0..rows {
+        let mut i = 0;
+        while i < cols {
+            if !covered[j][i] {
+                i += 1;
+                continue;
+            }
+            let mut i1 = i;
+            while i1 < cols && covered[j][i1] {
+                i1 += 1;
+            }
+            let mut j1 = j + 1;
+            while j1 < rows && covered[j1][i..i1].iter().all(|&c| c) {
+                j1 += 1;
+            }
+            for row in &mut covered[j..j1] {
+                for cell in &mut row[i..i1] {
+                    *cell = false;
+                }
+            }
+            merged.push(Rect {
+                x: xs[i],
+                y: ys[j],
+                w: xs[i1] - xs[i],
+                h: ys[j1] - ys[j],
+            });
+            i = i1;
+        }
+    }
+    merged
+}
+
+/// Smallest rectangle enclosing every obstacle, or `None` if there are none.
+pub fn obstacles_bounds(obstacles: &[Rect]) -> Option<Rect> {
+    let first = obstacles.first()?;
+    let (mut x0, mut y0) = (first.x, first.y);
+    let (mut x1, mut y1) = (first.x + first.w, first.y + first.h);
+    for r in &obstacles[1..] {
+        x0 = x0.min(r.x);
+        y0 = y0.min(r.y);
+        x1 = x1.max(r.x + r.w);
+        y1 = y1.max(r.y + r.h);
+    }
+    Some(Rect { x: x0, y: y0, w: x1 - x0, h: y1 - y0 })
+}
+
+/// Splits a span `[start, start + len)` into at most two pieces inside `[0, n)`,
+/// wrapping around the seam.
+fn wrap_span(start: i32, len: i32, n: i32) -> Vec<(i32, i32)> {
+    if len >= n {
+        return vec![(0, n)];
+    }
+    let s = start.rem_euclid(n);
+    if s + len <= n {
+        vec![(s, len)]
+    } else {
+        vec![(s, n - s), (0, s + len - n)]
+    }
+}
+
+/// Maps obstacles onto a toroidal `n×n` grid: anything extending past an edge
+/// re-enters from the opposite edge, so a rectangle spanning a seam becomes up to
+/// four in-bounds pieces.
+pub fn wrap_obstacles(obstacles: &[Rect], n: i32) -> Vec<Rect> {
+    let mut out = Vec::new();
+    for r in obstacles.iter().filter(|r| r.w > 0 && r.h > 0) {
+        for &(x, w) in &wrap_span(r.x, r.w, n) {
+            for &(y, h) in &wrap_span(r.y, r.h, n) {
+                out.push(Rect { x, y, w, h });
+            }
+        }
+    }
+    out
+}
+
+/// Rigid transform of a square grid onto itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transform {
+    /// Mirror left to right: `x` becomes `n - 1 - x`.
+    MirrorX,
+    /// Mirror bottom to top: `y` becomes `n - 1 - y`.
+    MirrorY,
+    /// Quarter turn counter-clockwise: cell `(x, y)` moves to `(n - 1 - y, x)`.
+    Rotate90,
+    Rotate180,
+    /// Quarter turn clockwise: cell `(x, y)` moves to `(y, n - 1 - x)`.
+    Rotate270,
+}
+
+/// Applies `transform` to the obstacles of an `n×n` map, e.g. to check that plans do
+/// not depend on orientation. Returns the grid side, unchanged since the grid is
+/// square, and the moved obstacles in input order. Move start and goal with
+/// [`transform_cell`] to keep queries consistent. On power-of-two grids every
+/// transform maps quadtree cells onto quadtree cells, so plan costs are preserved.
+pub fn transform_map(n: i32, obstacles: &[Rect], transform: Transform) -> (i32, Vec<Rect>) {
+    let moved = obstacles
+        .iter()
+        .map(|r| match transform {
+            Transform::MirrorX => Rect { x: n - r.x - r.w, ..*r },
+            Transform::MirrorY => Rect { y: n - r.y - r.h, ..*r },
+            Transform::Rotate90 => Rect { x: n - r.y - r.h, y: r.x, w: r.h, h: r.w },
+            Transform::Rotate180 => Rect { x: n - r.x - r.w, y: n - r.y - r.h, ..*r },
+            Transform::Rotate270 => Rect { x: r.y, y: n - r.x - r.w, w: r.h, h: r.w },
+        })
+        .collect();
+    (n, moved)
+}
+
+/// Where the unit cell at `(x, y)` of an `n×n` grid ends up under `transform`, as in
+/// [`transform_map`].
+pub fn transform_cell(n: i32, (x, y): (i32, i32), transform: Transform) -> (i32, i32) {
+    let (_, moved) = transform_map(n, &[Rect { x, y, w: 1, h: 1 }], transform);
+    (moved[0].x, moved[0].y)
+}
+
+/// Grows every obstacle by `margin_x` cells on its left and right sides and by
+/// `margin_y` cells on its bottom and top, clamped to the `n×n` grid. Planning
+/// against the result keeps a robot of half-extent `(margin_x, margin_y)` clear of
+/// the original obstacles. Negative margins are treated as zero.
+pub fn inflate_obstacles(obstacles: &[Rect], margin_x: i32, margin_y: i32, n: i32) -> Vec<Rect> {
+    let (mx, my) = (margin_x.max(0), margin_y.max(0));
+    obstacles
+        .iter()
+        .filter(|r| r.w > 0 && r.h > 0)
+        .map(|r| {
+            let x0 = (r.x - mx).max(0);
+            let y0 = (r.y - my).max(0);
+            let x1 = (r.x + r.w + mx).min(n);
+            let y1 = (r.y + r.h + my).min(n);
+            Rect { x: x0, y: y0, w: x1 - x0, h: y1 - y0 }
+        })
+        .filter(|r| r.w > 0 && r.h > 0)
+        .collect()
+}
+
+/// Distance from each region to its nearest obstacle (see [`rect_distance`]), indexed
+/// like `regions`. `f64::INFINITY` when there are no obstacles.
+pub fn region_clearance(regions: &[Rect], obstacles: &[Rect]) -> Vec<f64> {
+    regions
+        .iter()
+        .map(|r| {
+            obstacles
+                .iter()
+                .map(|obs| rect_distance(r, obs))
+                .fold(f64::INFINITY, f64::min)
+        })
+        .collect()
+}
+
+/// Moves the center of each region `shift` cells straight away from the nearest
+/// point of the closest obstacle within one region side, keeping it inside the
+/// middle half of the region. Centers are keyed by index into `regions`; regions
+/// with no obstacle that close keep their center.
+pub fn push_centers(
+    regions: &[Rect],
+    obstacles: &[Rect],
+    centers: &mut HashMap<usize, (f64, f64)>,
+    shift: f64,
+) {
+    for (id, r) in regions.iter().enumerate() {
+        let Some(c) = centers.get_mut(&id) else {
+            continue;
+        };
+        let (cx, cy) = *c;
+        let nearest = obstacles
+            .iter()
+            .map(|obs| {
+                let qx = cx.clamp(obs.x as f64, (obs.x + obs.w) as f64);
+                let qy = cy.clamp(obs.y as f64, (obs.y + obs.h) as f64);
+                (cx - qx, cy - qy)
+            })
+            .map(|(dx, dy)| (dx, dy, (dx * dx + dy * dy).sqrt()))
+            .filter(|&(_, _, d)| d > 0.0 && d <= r.w as f64)
+            .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
+        let Some((dx, dy, d)) = nearest else {
+            continue;
+        };
+
+        let (qw, qh) = (r.w as f64 / 4.0, r.h as f64 / 4.0);
+        let (x0, y0) = (r.x as f64, r.y as f64);
+        *c = (
+            (cx + shift * dx / d).clamp(x0 + qw, x0 + 3.0 * qw),
+            (cy + shift * dy / d).clamp(y0 + qh, y0 + 3.0 * qh),
+        );
+    }
+}
+
+/// Snaps a rectangle with fractional bounds onto the integer grid conservatively:
+/// the left and bottom edges are floored and the right and top edges ceiled, so the
+/// result always contains the input and free space is never overstated. Integer
+/// bounds are unchanged. Returns `None` for non-finite values, negative sizes, or
+/// bounds that do not fit in `i32`.
+pub fn snap_outward(x: f64, y: f64, w: f64, h: f64) -> Option<Rect> {
+    if ![x, y, w, h].iter().all(|v| v.is_finite()) || w < 0.0 || h < 0.0 {
+        return None;
+    }
+    let (x0, y0) = (x.floor(), y.floor());
+    let (x1, y1) = ((x + w).ceil(), (y + h).ceil());
+    let fits = |v: f64| v >= i32::MIN as f64 && v <= i32::MAX as f64;
+    if ![x0, y0, x1, y1, x1 - x0, y1 - y0].into_iter().all(fits) {
+        return None;
+    }
+    Some(Rect {
+        x: x0 as i32,
+        y: y0 as i32,
+        w: (x1 - x0) as i32,
+        h: (y1 - y0) as i32,
+    })
+}
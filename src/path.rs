@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+use crate::geometry::{segment_intersects, shared_border};
+use crate::models::rect::Rect;
+
+/// Returns true if the segment `a`–`b` crosses none of the obstacles.
+fn line_of_sight(a: (f64, f64), b: (f64, f64), obstacles: &[Rect]) -> bool {
+    !obstacles.iter().any(|obs| segment_intersects(obs, a, b))
+}
+
+/// Closest point to `p` on the polyline `line`.
+fn closest_on_polyline(p: (f64, f64), line: &[(f64, f64)]) -> (f64, f64) {
+    let mut best = line[0];
+    let mut best_d = f64::INFINITY;
+    for seg in line.windows(2) {
+        let (a, b) = (seg[0], seg[1]);
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let len2 = dx * dx + dy * dy;
+        let t = if len2 == 0.0 {
+            0.0
+        } else {
+            (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).clamp(0.0, 1.0)
+        };
+        let q = (a.0 + t * dx, a.1 + t * dy);
+        let d = (q.0 - p.0).powi(2) + (q.1 - p.1).powi(2);
+        if d < best_d {
+            best_d = d;
+            best = q;
+        }
+    }
+    best
+}
+
+/// Greedy line-of-sight shortcutting: from each kept waypoint, jumps to the
+/// farthest later waypoint that is directly visible.
+pub fn shortcut_path(waypoints: &[(f64, f64)], obstacles: &[Rect]) -> Vec<(f64, f64)> {
+    if waypoints.len() <= 2 {
+        return waypoints.to_vec();
+    }
+
+    let mut out = vec![waypoints[0]];
+    let mut i = 0;
+    while i < waypoints.len() - 1 {
+        let mut j = waypoints.len() - 1;
+        while j > i + 1 && !line_of_sight(waypoints[i], waypoints[j], obstacles) {
+            j -= 1;
+        }
+        out.push(waypoints[j]);
+        i = j;
+    }
+    out
+}
+
+/// Smooths a region-center path, trading obstacle clearance against length.
+///
+/// `center_bias` is clamped to `[0, 1]`:
+/// - `0.0` returns the fully shortcut path ([`shortcut_path`]), the shortest route
+///   the pass can find, which may graze obstacle corners.
+/// - `1.0` returns the waypoints unchanged, keeping the route on region centers,
+///   which stay as far from obstacles as the decomposition allows.
+///
+/// In between, each interior waypoint is moved from its nearest point on the
+/// shortcut path towards its region center by `center_bias`. A moved point is kept
+/// only if neither the segment reaching it nor the one on to the next original
+/// waypoint crosses an obstacle; otherwise the original waypoint stays. So every
+/// output segment is collision-free as long as the input polyline is.
+pub fn smooth_path(waypoints: &[(f64, f64)], obstacles: &[Rect], center_bias: f64) -> Vec<(f64, f64)> {
+    let bias = center_bias.clamp(0.0, 1.0);
+    let shortcut = shortcut_path(waypoints, obstacles);
+    if bias == 0.0 || waypoints.len() <= 2 {
+        return shortcut;
+    }
+    if bias == 1.0 {
+        return waypoints.to_vec();
+    }
+
+    let last = waypoints.len() - 1;
+    let mut out = vec![waypoints[0]];
+    for (i, &p) in waypoints.iter().enumerate().skip(1) {
+        if i == last {
+            out.push(p);
+            break;
+        }
+        let q = closest_on_polyline(p, &shortcut);
+        let blended = (q.0 + bias * (p.0 - q.0), q.1 + bias * (p.1 - q.1));
+        let prev = out[out.len() - 1];
+        if line_of_sight(prev, blended, obstacles) && line_of_sight(blended, waypoints[i + 1], obstacles) {
+            out.push(blended);
+        } else {
+            out.push(p);
+        }
+    }
+    out
+}
+
+/// Width of each passage along a region path: one `(from, to, width)` entry per
+/// consecutive region pair, where `width` is the length of their shared border.
+/// `regions[id]` is the rectangle of region `id`. Consecutive regions that do not
+/// share a border count as width `0`.
+pub fn corridor_widths(path: &[usize], regions: &[Rect]) -> Vec<(usize, usize, f64)> {
+    path.windows(2)
+        .map(|pair| {
+            let width = match shared_border(&regions[pair[0]], &regions[pair[1]]) {
+                Some((a, b)) => ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt(),
+                None => 0.0,
+            };
+            (pair[0], pair[1], width)
+        })
+        .collect()
+}
+
+/// Narrowest passage along a region path: the minimum width reported by
+/// [`corridor_widths`]. Returns `None` for paths with fewer than two regions.
+pub fn min_corridor_width(path: &[usize], regions: &[Rect]) -> Option<f64> {
+    corridor_widths(path, regions)
+        .into_iter()
+        .map(|(_, _, width)| width)
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+}
+
+/// Center of a region rectangle.
+fn rect_center(r: &Rect) -> (f64, f64) {
+    (r.x as f64 + r.w as f64 / 2.0, r.y as f64 + r.h as f64 / 2.0)
+}
+
+/// Point where the route from `a` to `b` should cross their shared border: where the
+/// center-to-center line meets the border, clamped onto the shared segment.
+/// Returns `None` if the regions do not share a border.
+pub fn border_crossing(a: &Rect, b: &Rect) -> Option<(f64, f64)> {
+    let (p, q) = shared_border(a, b)?;
+    let (c1, c2) = (rect_center(a), rect_center(b));
+    if p.0 == q.0 {
+        let t = (p.0 - c1.0) / (c2.0 - c1.0);
+        let y = (c1.1 + t * (c2.1 - c1.1)).clamp(p.1, q.1);
+        Some((p.0, y))
+    } else {
+        let t = (p.1 - c1.1) / (c2.1 - c1.1);
+        let x = (c1.0 + t * (c2.0 - c1.0)).clamp(p.0, q.0);
+        Some((x, p.1))
+    }
+}
+
+/// Waypoints for a region path that pass through the shared borders instead of the
+/// centers of intermediate regions: the first center, one [`border_crossing`] per
+/// transition, then the last center. Every leg stays inside a single free region, so
+/// the route remains collision-free while avoiding center-to-center doglegs.
+pub fn border_waypoints(path: &[usize], regions: &[Rect]) -> Vec<(f64, f64)> {
+    let Some((&first, &last)) = path.first().zip(path.last()) else {
+        return Vec::new();
+    };
+
+    let mut out = vec![rect_center(&regions[first])];
+    for pair in path.windows(2) {
+        let (a, b) = (&regions[pair[0]], &regions[pair[1]]);
+        match border_crossing(a, b) {
+            Some(p) => out.push(p),
+            None => out.push(rect_center(b)),
+        }
+    }
+    if path.len() > 1 {
+        out.push(rect_center(&regions[last]));
+    }
+    out
+}
+
+/// One region of a path with the points where the path enters and leaves it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RegionCrossing {
+    pub region: usize,
+    pub entry: (f64, f64),
+    pub exit: (f64, f64),
+}
+
+/// Splits a region path into gates for a controller: each region with its entry
+/// and exit on the shared borders (see [`border_crossing`]). The first region is
+/// entered and the last left at their centers, like [`border_waypoints`]; regions
+/// without a shared border (wrapped edges) fall back to centers as well.
+pub fn region_crossings(path: &[usize], regions: &[Rect]) -> Vec<RegionCrossing> {
+    let gate = |a: usize, b: usize| border_crossing(&regions[a], &regions[b]);
+    (0..path.len())
+        .map(|i| {
+            let id = path[i];
+            let center = rect_center(&regions[id]);
+            let entry = if i == 0 { None } else { gate(path[i - 1], id) };
+            let exit = path.get(i + 1).and_then(|&next| gate(id, next));
+            RegionCrossing {
+                region: id,
+                entry: entry.unwrap_or(center),
+                exit: exit.unwrap_or(center),
+            }
+        })
+        .collect()
+}
+
+/// Distance from `p` to the segment `a`–`b`.
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let q = closest_on_polyline(p, &[a, b]);
+    ((q.0 - p.0).powi(2) + (q.1 - p.1).powi(2)).sqrt()
+}
+
+/// Ramer–Douglas–Peucker simplification that stays collision-free: points within
+/// `epsilon` of a chord are dropped only if the chord itself crosses no obstacle;
+/// otherwise the farthest point is kept and both halves are simplified again.
+/// Endpoints are always kept.
+pub fn simplify_path(path: &[(f64, f64)], epsilon: f64, obstacles: &[Rect]) -> Vec<(f64, f64)> {
+    fn keep(path: &[(f64, f64)], i: usize, j: usize, epsilon: f64, obstacles: &[Rect], out: &mut Vec<(f64, f64)>) {
+        if j <= i + 1 {
+            out.push(path[j]);
+            return;
+        }
+        let (k, dmax) = (i + 1..j)
+            .map(|k| (k, segment_distance(path[k], path[i], path[j])))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .unwrap();
+        if dmax <= epsilon && line_of_sight(path[i], path[j], obstacles) {
+            out.push(path[j]);
+        } else {
+            keep(path, i, k, epsilon, obstacles, out);
+            keep(path, k, j, epsilon, obstacles, out);
+        }
+    }
+
+    if path.len() <= 2 {
+        return path.to_vec();
+    }
+    let mut out = vec![path[0]];
+    keep(path, 0, path.len() - 1, epsilon, obstacles, &mut out);
+    out
+}
+
+/// Displacement `(dx, dy)` from each region center on `path` to the next, one fewer
+/// than the regions; summed they give the vector from start to goal.
+pub fn path_deltas(path: &[usize], centers: &HashMap<usize, (f64, f64)>) -> Vec<(f64, f64)> {
+    path.windows(2)
+        .map(|w| {
+            let (a, b) = (centers[&w[0]], centers[&w[1]]);
+            (b.0 - a.0, b.1 - a.1)
+        })
+        .collect()
+}
+
+/// Number of direction changes along the region path and the sum of their absolute
+/// turning angles in radians, measured between consecutive center-to-center legs.
+/// Legs of zero length are skipped.
+pub fn path_turns(path: &[usize], centers: &HashMap<usize, (f64, f64)>) -> (usize, f64) {
+    let legs: Vec<(f64, f64)> = path_deltas(path, centers)
+        .into_iter()
+        .filter(|&(dx, dy)| dx != 0.0 || dy != 0.0)
+        .collect();
+
+    let mut turns = 0;
+    let mut total = 0.0;
+    for pair in legs.windows(2) {
+        let ((x1, y1), (x2, y2)) = (pair[0], pair[1]);
+        let angle = (x1 * y2 - y1 * x2).atan2(x1 * x2 + y1 * y2).abs();
+        if angle > 1e-9 {
+            turns += 1;
+            total += angle;
+        }
+    }
+    (turns, total)
+}
+
+/// Annotates each waypoint with its arrival time when driving the polyline at a
+/// constant `speed`, leaving the first waypoint at `start_time`.
+/// Returns `None` unless `speed` is positive and finite.
+pub fn timestamp_path(waypoints: &[(f64, f64)], speed: f64, start_time: f64) -> Option<Vec<(f64, f64, f64)>> {
+    if speed <= 0.0 || !speed.is_finite() {
+        return None;
+    }
+    let mut t = start_time;
+    let mut out = Vec::with_capacity(waypoints.len());
+    for (i, &(x, y)) in waypoints.iter().enumerate() {
+        if i > 0 {
+            let (px, py) = waypoints[i - 1];
+            t += ((x - px).powi(2) + (y - py).powi(2)).sqrt() / speed;
+        }
+        out.push((x, y, t));
+    }
+    Some(out)
+}
+
+/// Distance from `p` to the nearest obstacle or edge of the `n×n` grid.
+fn point_clearance(p: (f64, f64), obstacles: &[Rect], n: i32) -> f64 {
+    let edge = p.0.min(p.1).min(n as f64 - p.0).min(n as f64 - p.1);
+    obstacles.iter().fold(edge, |best, r| {
+        let dx = (r.x as f64 - p.0).max(p.0 - (r.x + r.w) as f64).max(0.0);
+        let dy = (r.y as f64 - p.1).max(p.1 - (r.y + r.h) as f64).max(0.0);
+        best.min((dx * dx + dy * dy).sqrt())
+    })
+}
+
+/// The candidate with the most clearance; ties go to the one nearest `anchor`.
+fn most_clear(candidates: Vec<(f64, f64)>, anchor: (f64, f64), obstacles: &[Rect], n: i32) -> (f64, f64) {
+    let key = |p: (f64, f64)| {
+        let d2 = (p.0 - anchor.0).powi(2) + (p.1 - anchor.1).powi(2);
+        (point_clearance(p, obstacles, n), -d2)
+    };
+    candidates
+        .into_iter()
+        .max_by(|&a, &b| key(a).partial_cmp(&key(b)).unwrap())
+        .unwrap_or(anchor)
+}
+
+/// Points strictly between `from` and `from + len`, every half cell.
+fn half_steps(from: f64, len: f64) -> impl Iterator<Item = f64> {
+    (1..(2.0 * len) as i32).map(move |k| from + k as f64 / 2.0)
+}
+
+/// Waypoints for a region path that follow the middle of corridors, an approximate
+/// medial axis of the free space: in every region, the point farthest from obstacles
+/// and the grid edge, and on every shared border between consecutive regions, the
+/// crossing point farthest from them. Candidates are sampled every half cell; ties
+/// go to the region center or border midpoint. Like [`border_waypoints`], every leg
+/// stays inside a single free region. Regions without a shared border (wrapped
+/// edges) are joined directly. Cost grows with the area of the regions on the path.
+pub fn medial_waypoints(path: &[usize], regions: &[Rect], obstacles: &[Rect], n: i32) -> Vec<(f64, f64)> {
+    let mut out = Vec::with_capacity(2 * path.len());
+    for (i, &id) in path.iter().enumerate() {
+        if i > 0 {
+            if let Some((a, b)) = shared_border(&regions[path[i - 1]], &regions[id]) {
+                let border = if a.0 == b.0 {
+                    half_steps(a.1, b.1 - a.1).map(|y| (a.0, y)).collect()
+                } else {
+                    half_steps(a.0, b.0 - a.0).map(|x| (x, a.1)).collect()
+                };
+                let mid = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+                out.push(most_clear(border, mid, obstacles, n));
+            }
+        }
+        let r = &regions[id];
+        let (x, y, w, h) = (r.x as f64, r.y as f64, r.w as f64, r.h as f64);
+        let cells = half_steps(x, w).flat_map(|px| half_steps(y, h).map(move |py| (px, py))).collect();
+        out.push(most_clear(cells, rect_center(r), obstacles, n));
+    }
+    out
+}
+
+/// Point where the segments `a`–`b` and `c`–`d` meet, if they do. Parallel segments
+/// never count as meeting.
+fn segment_crossing(a: (f64, f64), b: (f64, f64), c: (f64, f64), d: (f64, f64)) -> Option<(f64, f64)> {
+    let (r, s) = ((b.0 - a.0, b.1 - a.1), (d.0 - c.0, d.1 - c.1));
+    let denom = r.0 * s.1 - r.1 * s.0;
+    if denom.abs() < 1e-12 {
+        return None;
+    }
+    let (qx, qy) = (c.0 - a.0, c.1 - a.1);
+    let t = (qx * s.1 - qy * s.0) / denom;
+    let u = (qx * r.1 - qy * r.0) / denom;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some((a.0 + t * r.0, a.1 + t * r.1))
+}
+
+/// Removes the loops of a self-crossing polyline, e.g. after smoothing: wherever two
+/// non-consecutive segments meet, everything between them is replaced by the
+/// meeting point, the earliest segment being cut and the widest loop removed first.
+/// The remaining legs are parts of the original ones, so a collision-free polyline
+/// stays collision-free. Polylines that never cross themselves are returned as is.
+pub fn remove_loops(waypoints: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut out = waypoints.to_vec();
+    'cut: loop {
+        for i in 0..out.len().saturating_sub(1) {
+            for j in (i + 2..out.len() - 1).rev() {
+                if let Some(p) = segment_crossing(out[i], out[i + 1], out[j], out[j + 1]) {
+                    let keep = p != out[i] && p != out[j + 1];
+                    out.splice(i + 1..=j, keep.then_some(p));
+                    continue 'cut;
+                }
+            }
+        }
+        return out;
+    }
+}
@@ -0,0 +1,1140 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::algo::{
+    astar, bidirectional_dijkstra, dijkstra_excluding, dijkstra_to_any, dijkstra_until,
+    greedy_best_first, resource_constrained_path, time_expanded_path, widest_path, SearchResult,
+};
+use crate::cache::{extend_checksum, map_checksum, CacheError, GraphCache};
+use crate::geometry::{covers, intersects, segment_intersects, shape_covers};
+use crate::graph::{
+    apply_axis_scale, apply_clearance_weighting, apply_costs, apply_heading_bias, apply_size_penalty,
+    border_width_graph, build_graph_ordered, connect_visible, connect_wrapped, find_k_nearest, limit_degree,
+    set_edge_weight, snap, ClearanceWeighting,
+};
+use crate::input::{read_input, InputError};
+use crate::path::{border_waypoints, medial_waypoints, region_crossings, remove_loops, smooth_path, RegionCrossing};
+use crate::models::map::Map;
+use crate::models::quad::{Quad, QuadrantOrder};
+use crate::models::rect::Rect;
+use crate::models::shape::Shape;
+use crate::models::terrain::Terrain;
+use crate::obstacles::{inflate_obstacles, merge_obstacles, push_centers, region_clearance, wrap_obstacles};
+use crate::quadtree::{
+    build_quad_adaptive_until, build_quad_auto, build_quad_from_free, build_terrain_quad_until,
+    collect_free_ordered, density_min_side, free_leaves_ordered, merge_free_leaves, terrain_costs,
+    RECURSIVE_BUILD_LIMIT,
+};
+
+/// Why a query found no path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoPathReason {
+    /// The requested start point lies inside an obstacle.
+    StartBlocked,
+    /// The requested goal point lies inside an obstacle.
+    GoalBlocked,
+    /// Both points are free but in different connected components of the map.
+    Disconnected,
+    /// A point lies outside the area a query was confined to (see
+    /// [`Planner::query_within`]).
+    OutsideArea,
+}
+
+/// Everything that can go wrong between reading a map and returning a path.
+#[derive(Debug)]
+pub enum PlanError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+    /// A query point lies outside the `n×n` grid.
+    InvalidBounds { x: i32, y: i32, n: i32 },
+    /// The obstacles cover the whole grid.
+    NoFreeSpace,
+    NoPath(NoPathReason),
+    /// A query point lies inside `obstacle` and [`PlanOptions::reject_blocked_endpoints`]
+    /// is set.
+    InsideObstacle { x: i32, y: i32, obstacle: Rect },
+    /// The deadline given to [`plan_with_timeout`] passed first, or
+    /// [`PlanOptions::cancel`] was raised.
+    Timeout,
+    /// No obstacle or terrain carries this label (see [`label_center`]).
+    UnknownLabel(String),
+    /// More than one obstacle or terrain entry carries this label.
+    AmbiguousLabel { label: String, count: usize },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::Io(e) => write!(f, "io error: {}", e),
+            PlanError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            PlanError::InvalidBounds { x, y, n } => {
+                write!(f, "point ({}, {}) is outside the {}x{} grid", x, y, n, n)
+            }
+            PlanError::NoFreeSpace => write!(f, "no free region in the grid"),
+            PlanError::NoPath(NoPathReason::StartBlocked) => {
+                write!(f, "no path: the start point is inside an obstacle")
+            }
+            PlanError::NoPath(NoPathReason::GoalBlocked) => {
+                write!(f, "no path: the goal point is inside an obstacle")
+            }
+            PlanError::NoPath(NoPathReason::Disconnected) => {
+                write!(f, "no path: start and goal are in disconnected areas")
+            }
+            PlanError::NoPath(NoPathReason::OutsideArea) => {
+                write!(f, "no path: an endpoint lies outside the allowed area")
+            }
+            PlanError::InsideObstacle { x, y, obstacle } => write!(
+                f,
+                "point ({}, {}) is inside the obstacle at ({}, {}) of size {}x{}",
+                x, y, obstacle.x, obstacle.y, obstacle.w, obstacle.h
+            ),
+            PlanError::Timeout => write!(f, "planning did not finish before the deadline"),
+            PlanError::UnknownLabel(label) => write!(f, "no region is labeled {:?}", label),
+            PlanError::AmbiguousLabel { label, count } => {
+                write!(f, "{} regions are labeled {:?}", count, label)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PlanError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<InputError> for PlanError {
+    fn from(e: InputError) -> Self {
+        match e {
+            InputError::Io(e) => PlanError::Io(e),
+            InputError::Parse { line, message } => PlanError::Parse { line, message },
+        }
+    }
+}
+
+/// Search algorithm used by [`Planner::query`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Optimal shortest path.
+    #[default]
+    Dijkstra,
+    /// Fast, possibly suboptimal path (see [`greedy_best_first`]).
+    Greedy,
+    /// Optimal path with fewer expansions (see [`astar`]), using
+    /// [`PlanOptions::axis_scale`] as the heuristic scale. Terrain costs below 1 or
+    /// wrapping make the heuristic overestimate, and the path may then be suboptimal.
+    AStar,
+    /// Optimal path searched from both ends (see [`bidirectional_dijkstra`]).
+    Bidirectional,
+}
+
+/// Preference for moving along `direction`, see [`apply_heading_bias`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeadingBias {
+    pub direction: (f64, f64),
+    /// Extra cost per unit length of moving straight against `direction`.
+    pub weight: f64,
+}
+
+/// Tunables for building and querying a [`Planner`].
+#[derive(Clone, Debug, Default)]
+pub struct PlanOptions {
+    /// When set, waypoints are post-processed by [`smooth_path`] with this center bias:
+    /// `0.0` favours the shortest line-of-sight route, `1.0` keeps region centers.
+    pub center_bias: Option<f64>,
+    /// Route through the shared borders between regions rather than through every
+    /// region center (see [`border_waypoints`]). Applied before smoothing.
+    pub border_crossings: bool,
+    /// Route along the middle of corridors, through the points of each region and
+    /// border farthest from obstacles (see [`medial_waypoints`]). Takes precedence
+    /// over `border_crossings`; applied before smoothing.
+    pub medial_axis: bool,
+    /// Cut out the loops of waypoint polylines that cross themselves after
+    /// post-processing (see [`remove_loops`]). Applied last.
+    pub remove_loops: bool,
+    pub search: SearchMode,
+    /// Smallest improvement that counts when relaxing edges in [`SearchMode::Dijkstra`]
+    /// (see [`dijkstra_epsilon`](crate::algo::dijkstra_epsilon)). `0.0`, the default, compares exactly; larger values
+    /// cut redundant relaxations at the price of optimality.
+    pub epsilon: f64,
+    /// Child visiting order used to number regions. Changes ids and tie-breaking
+    /// between equal-cost paths, never the optimal cost.
+    pub order: QuadrantOrder,
+    /// Merge touching and overlapping obstacles with [`merge_obstacles`] before
+    /// building the tree. Same blocked area, fewer rectangles to test.
+    pub merge_obstacles: bool,
+    /// Treat the grid as a torus: obstacles past an edge re-enter on the opposite
+    /// side and regions on opposite edges are linked. Waypoint post-processing does
+    /// not know about the seam, so leave smoothing off when wrapping.
+    pub wrap: bool,
+    /// Also link every pair of regions within this center distance that have line
+    /// of sight (see [`connect_visible`]). Denser graph, straighter paths.
+    pub look_radius: Option<f64>,
+    /// Collapse splits whose four children are all free (see [`merge_free_leaves`]).
+    /// Skipped when the map has penalty terrain, whose leaves must keep their costs.
+    pub merge_leaves: bool,
+    /// Penalise edges that point away from a preferred heading, e.g. to keep paths
+    /// on a road from weaving backwards.
+    pub heading_bias: Option<HeadingBias>,
+    /// Clearance `(x, y)` in cells added around every obstacle before building (see
+    /// [`inflate_obstacles`]), for a robot footprint longer on one axis.
+    pub inflate: (i32, i32),
+    /// Scale edges by `1 + alpha / mean cell size` (see [`apply_size_penalty`]) to
+    /// favour large open cells over the fine subdivisions near obstacles.
+    pub size_penalty: Option<f64>,
+    /// Fail with [`PlanError::InsideObstacle`] when a start or goal point lies inside
+    /// an obstacle, instead of snapping it to the nearest free region.
+    pub reject_blocked_endpoints: bool,
+    /// Per-axis cost of movement `(sx, sy)`, e.g. `(1.0, 3.0)` when climbing costs
+    /// three times as much as moving sideways (see [`apply_axis_scale`]). The same
+    /// scale drives the [`SearchMode::AStar`] heuristic, which keeps it admissible.
+    pub axis_scale: Option<(f64, f64)>,
+    /// Keep at most this many edges per region, the shortest ones (see
+    /// [`limit_degree`]). Faster searches on dense graphs, possibly longer paths.
+    pub max_degree: Option<usize>,
+    /// Move region centers this many cells away from nearby obstacles before
+    /// building edges (see [`push_centers`]), so routes through centers keep some
+    /// margin without full smoothing.
+    pub center_push: Option<f64>,
+    /// Weight edges by obstacle clearance, to hug walls or keep away from them (see
+    /// [`apply_clearance_weighting`]).
+    pub clearance_weighting: Option<ClearanceWeighting>,
+    /// Resolution by obstacle density instead of uniform unit cells: `(count, side)`
+    /// entries as in [`density_min_side`], fed to [`build_quad_adaptive`]. Partly
+    /// blocked cells at the cutoff count as blocked. Ignored when the map has
+    /// penalty terrain.
+    pub adaptive_sides: Option<Vec<(usize, i32)>>,
+    /// Largest grid side built recursively; larger maps are built with an explicit
+    /// stack (see [`build_quad_auto`]). `None` uses [`RECURSIVE_BUILD_LIMIT`]. Maps
+    /// with penalty terrain, `adaptive_sides` or a `cancel` flag use their own builders.
+    pub recursive_build_limit: Option<i32>,
+    /// Flag polled while building the tree and graph and during
+    /// [`SearchMode::Dijkstra`] searches; once raised, the build or query stops and
+    /// fails with [`PlanError::Timeout`]. [`plan_with_timeout`] sets its own.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+/// Timings and sizes collected while planning.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlanStats {
+    pub build_time: Duration,
+    pub graph_time: Duration,
+    pub search_time: Duration,
+    pub regions: usize,
+    pub edges: usize,
+    pub expansions: usize,
+}
+
+impl PlanStats {
+    /// Compact JSON object with the timings in seconds, e.g. for CI performance checks.
+    pub fn to_json(&self) -> String {
+        format!(
+            concat!(
+                "{{\"build_time\":{},\"graph_time\":{},\"search_time\":{},",
+                "\"regions\":{},\"edges\":{},\"expansions\":{}}}"
+            ),
+            self.build_time.as_secs_f64(),
+            self.graph_time.as_secs_f64(),
+            self.search_time.as_secs_f64(),
+            self.regions,
+            self.edges,
+            self.expansions
+        )
+    }
+}
+
+/// A successful plan: graph cost, region ids from start to goal, and the waypoint
+/// polyline through their centers, or their borders when [`PlanOptions::border_crossings`]
+/// is set, smoothed when [`PlanOptions::center_bias`] is set.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlanResult {
+    pub cost: f64,
+    pub path: Vec<usize>,
+    pub waypoints: Vec<(f64, f64)>,
+    /// Distance from the requested start point to the center of the region it snapped to.
+    pub start_snap: f64,
+    /// Distance from the requested goal point to the center of the region it snapped to.
+    pub goal_snap: f64,
+    pub stats: PlanStats,
+    /// Rectangle of every region on `path`, so the path can be checked against a
+    /// rebuilt planner whose ids may differ (see [`Planner::replan`]).
+    pub regions: Vec<Rect>,
+    /// Grid side length of the map the plan was made on.
+    pub n: i32,
+    /// Obstacles of that map at query time, so the plan can be rendered or checked
+    /// without the planner.
+    pub obstacles: Vec<Rect>,
+}
+
+impl PlanResult {
+    /// Returns true if either endpoint was moved further than `threshold` when
+    /// snapping, e.g. because it sat inside an obstacle or deep in a large region.
+    pub fn snap_exceeds(&self, threshold: f64) -> bool {
+        self.start_snap > threshold || self.goal_snap > threshold
+    }
+
+    /// Side of every region on the path, start to goal. A long run of small cells
+    /// means the path threads close to obstacles, where a coarser `min_size` or
+    /// smoothing may straighten it.
+    pub fn cell_sizes(&self) -> Vec<i32> {
+        self.regions.iter().map(|r| r.w).collect()
+    }
+}
+
+/// Obstacles as the tree sees them: wrapped, inflated and merged per `options`.
+fn blocking_obstacles(n: i32, obstacles: &[Rect], options: &PlanOptions) -> Vec<Rect> {
+    let mut blocking = obstacles.to_vec();
+    if options.wrap {
+        blocking = wrap_obstacles(&blocking, n);
+    }
+    if options.inflate != (0, 0) {
+        blocking = inflate_obstacles(&blocking, options.inflate.0, options.inflate.1, n);
+    }
+    if options.merge_obstacles {
+        blocking = merge_obstacles(&blocking);
+    }
+    blocking
+}
+
+/// Fails with [`PlanError::Timeout`] once [`PlanOptions::cancel`] is raised.
+fn check_cancel(options: &PlanOptions) -> Result<(), PlanError> {
+    match &options.cancel {
+        Some(flag) if flag.load(Ordering::Relaxed) => Err(PlanError::Timeout),
+        _ => Ok(()),
+    }
+}
+
+/// Quadtree of a map plus the preprocessed obstacles and terrain it was built from.
+struct Tree {
+    quad: Quad,
+    blocking: Vec<Rect>,
+    cells: Vec<Terrain>,
+    build_time: Duration,
+}
+
+impl Tree {
+    fn build(n: i32, obstacles: &[Rect], terrain: &[Terrain], options: &PlanOptions) -> Result<Tree, PlanError> {
+        let t = Instant::now();
+        let blocking = blocking_obstacles(n, obstacles, options);
+        let mut cells = terrain.to_vec();
+        if options.wrap {
+            cells = terrain
+                .iter()
+                .flat_map(|t| {
+                    let cost = t.cost;
+                    wrap_obstacles(&[t.rect], n).into_iter().map(move |rect| Terrain { rect, cost })
+                })
+                .collect();
+        }
+        let stop = || check_cancel(options).is_err();
+        let mut quad = match &options.adaptive_sides {
+            Some(table) if cells.is_empty() => {
+                build_quad_adaptive_until(&blocking, 0, 0, n, &|k| density_min_side(table, k), &stop)
+            }
+            _ if cells.is_empty() && options.cancel.is_none() => {
+                let limit = options.recursive_build_limit.unwrap_or(RECURSIVE_BUILD_LIMIT);
+                Some(build_quad_auto(&blocking, 0, 0, n, limit))
+            }
+            _ => build_terrain_quad_until(&blocking, &cells, 0, 0, n, &stop),
+        }
+        .ok_or(PlanError::Timeout)?;
+        if options.merge_leaves && cells.is_empty() {
+            quad = merge_free_leaves(quad);
+        }
+        Ok(Tree {
+            quad,
+            blocking,
+            cells,
+            build_time: t.elapsed(),
+        })
+    }
+}
+
+/// Quadtree, free-region centers and region graph built once for a map,
+/// ready to answer any number of start/goal queries.
+pub struct Planner {
+    n: i32,
+    obstacles: Vec<Rect>,
+    terrain: Vec<Terrain>,
+    /// `obstacles` after wrapping, inflation and merging, as the tree was built from;
+    /// waypoint post-processing checks against these.
+    blocking: Vec<Rect>,
+    options: PlanOptions,
+    checksum: u64,
+    quad: Quad,
+    regions_rebuilt: bool,
+    regions: Vec<Rect>,
+    centers: HashMap<usize, (f64, f64)>,
+    graph: Vec<Vec<(usize, f64)>>,
+    stats: PlanStats,
+}
+
+impl Planner {
+    /// Builds the quadtree and region graph for an `n×n` grid.
+    /// Fails with [`PlanError::NoFreeSpace`] when no free cell remains.
+    pub fn new(n: i32, obstacles: &[Rect], options: &PlanOptions) -> Result<Planner, PlanError> {
+        Planner::with_terrain(n, obstacles, &[], options)
+    }
+
+    /// Like [`Planner::new`], with penalty `terrain` scaling the cost of the regions it covers.
+    pub fn with_terrain(
+        n: i32,
+        obstacles: &[Rect],
+        terrain: &[Terrain],
+        options: &PlanOptions,
+    ) -> Result<Planner, PlanError> {
+        let tree = Tree::build(n, obstacles, terrain, options)?;
+        Planner::from_tree(n, obstacles, terrain, options, tree)
+    }
+
+    /// Extracts regions and builds the graph of an already built tree.
+    fn from_tree(
+        n: i32,
+        obstacles: &[Rect],
+        terrain: &[Terrain],
+        options: &PlanOptions,
+        tree: Tree,
+    ) -> Result<Planner, PlanError> {
+        let Tree { quad, blocking, cells, build_time } = tree;
+        let t = Instant::now();
+        let mut centers = HashMap::new();
+        let mut id = 0;
+        collect_free_ordered(&quad, options.order, &mut id, &mut centers);
+        if centers.is_empty() {
+            return Err(PlanError::NoFreeSpace);
+        }
+
+        let regions = free_leaves_ordered(&quad, options.order);
+        if let Some(shift) = options.center_push {
+            push_centers(&regions, &blocking, &mut centers, shift);
+        }
+
+        let mut graph = vec![vec![]; centers.len()];
+        let mut id = 0;
+        build_graph_ordered(&quad, options.order, &mut id, &mut graph, &centers);
+        check_cancel(options)?;
+        if options.wrap {
+            connect_wrapped(n, &regions, &mut graph, &centers);
+        }
+        if let Some(radius) = options.look_radius {
+            connect_visible(radius, &blocking, &mut graph, &centers);
+            check_cancel(options)?;
+        }
+        if let Some(k) = options.max_degree {
+            limit_degree(&mut graph, k);
+        }
+        if let Some(scale) = options.axis_scale {
+            apply_axis_scale(&mut graph, &centers, scale);
+        }
+        if !cells.is_empty() {
+            apply_costs(&mut graph, &terrain_costs(&regions, &cells));
+        }
+        if let Some(mode) = options.clearance_weighting {
+            apply_clearance_weighting(&mut graph, &region_clearance(&regions, &blocking), mode);
+        }
+        if let Some(alpha) = options.size_penalty {
+            apply_size_penalty(&mut graph, &regions, alpha);
+        }
+        if let Some(bias) = options.heading_bias {
+            apply_heading_bias(&mut graph, &centers, bias.direction, bias.weight);
+        }
+        let graph_time = t.elapsed();
+
+        let stats = PlanStats {
+            build_time,
+            graph_time,
+            regions: centers.len(),
+            edges: graph.iter().map(Vec::len).sum::<usize>() / 2,
+            ..PlanStats::default()
+        };
+        log::debug!(
+            "built {} regions and {} edges (tree {:?}, graph {:?})",
+            stats.regions,
+            stats.edges,
+            build_time,
+            graph_time
+        );
+
+        Ok(Planner {
+            n,
+            obstacles: obstacles.to_vec(),
+            terrain: terrain.to_vec(),
+            blocking,
+            options: options.clone(),
+            checksum: graph_checksum(n, obstacles, terrain, options),
+            quad,
+            regions_rebuilt: true,
+            regions,
+            centers,
+            graph,
+            stats,
+        })
+    }
+
+    /// Rebuilds a planner from a graph saved with [`Planner::to_cache`], skipping the
+    /// obstacle scan and graph construction. Fails with [`CacheError::Stale`] if the cache
+    /// was built for a different map or with different graph options (see
+    /// [`graph_checksum`]).
+    pub fn from_cache(
+        n: i32,
+        obstacles: &[Rect],
+        terrain: &[Terrain],
+        cache: GraphCache,
+        options: &PlanOptions,
+    ) -> Result<Planner, CacheError> {
+        let checksum = graph_checksum(n, obstacles, terrain, options);
+        if cache.checksum != checksum {
+            return Err(CacheError::Stale {
+                expected: checksum,
+                found: cache.checksum,
+            });
+        }
+
+        let stats = PlanStats {
+            regions: cache.centers.len(),
+            edges: cache.graph.iter().map(Vec::len).sum::<usize>() / 2,
+            ..PlanStats::default()
+        };
+        Ok(Planner {
+            n,
+            obstacles: obstacles.to_vec(),
+            terrain: terrain.to_vec(),
+            blocking: blocking_obstacles(n, obstacles, options),
+            options: options.clone(),
+            checksum,
+            // The cache only stores free leaves; rebuild a tree with the same leaves
+            quad: build_quad_from_free(&cache.regions, 0, 0, n),
+            regions_rebuilt: true,
+            regions: cache.regions,
+            centers: cache.centers,
+            graph: cache.graph,
+            stats,
+        })
+    }
+
+    /// Snapshot of the region graph and geometry, for [`crate::cache::save_graph`].
+    pub fn to_cache(&self) -> GraphCache {
+        GraphCache {
+            checksum: self.checksum,
+            regions: self.regions.clone(),
+            centers: self.centers.clone(),
+            graph: self.graph.clone(),
+        }
+    }
+
+    /// Build timings and graph size. Search fields stay zero; see [`PlanResult::stats`].
+    pub fn stats(&self) -> &PlanStats {
+        &self.stats
+    }
+
+    /// Grid side length.
+    pub fn size(&self) -> i32 {
+        self.n
+    }
+
+    /// Obstacles of the map, including any added with [`Planner::insert_obstacle`].
+    pub fn obstacles(&self) -> &[Rect] {
+        &self.obstacles
+    }
+
+    /// Penalty terrain of the map.
+    pub fn terrain(&self) -> &[Terrain] {
+        &self.terrain
+    }
+
+    /// Adds an obstacle. The tree is rebuilt, but regions, centers and graph are only
+    /// recomputed when it actually changed, e.g. not for an obstacle dropped into
+    /// space that is already blocked. A recomputed graph loses edge weights set with
+    /// [`Planner::set_edge_weight`]. Fails with [`PlanError::NoFreeSpace`], leaving the
+    /// planner unchanged, if the obstacle fills the last free space.
+    pub fn insert_obstacle(&mut self, obstacle: Rect) -> Result<(), PlanError> {
+        let mut obstacles = self.obstacles.clone();
+        obstacles.push(obstacle);
+        self.replace_obstacles(obstacles)
+    }
+
+    /// Removes the obstacle at `index` in [`Planner::obstacles`], returning it, or
+    /// `None` if `index` is out of range. Regions and graph are recomputed only if
+    /// the tree changed, as for [`Planner::insert_obstacle`]. Usually removing an
+    /// obstacle only frees space, but with [`PlanOptions::adaptive_sides`] fewer
+    /// obstacles can mean coarser cells; if that blocks the last free space this
+    /// fails with [`PlanError::NoFreeSpace`], leaving the planner unchanged.
+    pub fn remove_obstacle(&mut self, index: usize) -> Result<Option<Rect>, PlanError> {
+        if index >= self.obstacles.len() {
+            return Ok(None);
+        }
+        let mut obstacles = self.obstacles.clone();
+        let removed = obstacles.remove(index);
+        self.replace_obstacles(obstacles)?;
+        Ok(Some(removed))
+    }
+
+    /// Whether the last obstacle edit had to recompute regions and graph.
+    pub fn regions_rebuilt(&self) -> bool {
+        self.regions_rebuilt
+    }
+
+    fn replace_obstacles(&mut self, obstacles: Vec<Rect>) -> Result<(), PlanError> {
+        let tree = Tree::build(self.n, &obstacles, &self.terrain, &self.options)?;
+        // Visibility edges depend on the obstacles themselves, not just the tree
+        if tree.quad == self.quad && self.options.look_radius.is_none() {
+            self.checksum = graph_checksum(self.n, &obstacles, &self.terrain, &self.options);
+            self.obstacles = obstacles;
+            self.blocking = tree.blocking;
+            self.stats.build_time = tree.build_time;
+            self.regions_rebuilt = false;
+            return Ok(());
+        }
+        let terrain = std::mem::take(&mut self.terrain);
+        let rebuilt = Planner::from_tree(self.n, &obstacles, &terrain, &self.options, tree);
+        self.terrain = terrain;
+        *self = rebuilt?;
+        Ok(())
+    }
+
+    /// Root of the quadtree the regions were extracted from. For a planner loaded
+    /// with [`Planner::from_cache`] this is rebuilt from the cached regions: same free
+    /// leaves, though blocked areas may be stored as fewer, larger leaves.
+    pub fn quad(&self) -> &Quad {
+        &self.quad
+    }
+
+    /// Center of every free region, keyed by region id.
+    pub fn centers(&self) -> &HashMap<usize, (f64, f64)> {
+        &self.centers
+    }
+
+    /// Rectangle of every free region, indexed by region id.
+    pub fn regions(&self) -> &[Rect] {
+        &self.regions
+    }
+
+    /// Distance from every region, indexed by region id, to the nearest obstacle of
+    /// the map as given (before inflation). Computed on each call.
+    pub fn clearance(&self) -> Vec<f64> {
+        region_clearance(&self.regions, &self.obstacles)
+    }
+
+    /// Extent `(x, y, size)` of region `id` as a rectangle.
+    /// Panics if `id` is not a region id of this planner.
+    pub fn region_rect(&self, id: usize) -> Rect {
+        self.regions[id]
+    }
+
+    /// Adjacency list of the region graph.
+    pub fn graph(&self) -> &[Vec<(usize, f64)>] {
+        &self.graph
+    }
+
+    /// Entry and exit point of every region on `result`'s path (see [`region_crossings`]).
+    pub fn region_crossings(&self, result: &PlanResult) -> Vec<RegionCrossing> {
+        region_crossings(&result.path, &self.regions)
+    }
+
+    /// Sets the cost of moving from region `from` to region `to` without changing the
+    /// reverse direction, e.g. for slopes or one-way passages (`f64::INFINITY`).
+    /// Returns false if the regions are not adjacent.
+    pub fn set_edge_weight(&mut self, from: usize, to: usize, weight: f64) -> bool {
+        set_edge_weight(&mut self.graph, from, to, weight)
+    }
+
+    /// Snaps `start` and `goal` to their nearest free regions and searches between them.
+    pub fn query(&self, start: (i32, i32), goal: (i32, i32)) -> Result<PlanResult, PlanError> {
+        let t = Instant::now();
+        let (from, to, start_snap, goal_snap) = self.prepare(start, goal)?;
+        let found = match self.options.search {
+            SearchMode::Dijkstra => {
+                let stop = || check_cancel(&self.options).is_err();
+                dijkstra_until(&self.graph, from, to, self.options.epsilon, &stop)
+            }
+            SearchMode::Greedy => greedy_best_first(&self.graph, &self.centers, from, to),
+            SearchMode::AStar => {
+                let scale = self.options.axis_scale.unwrap_or((1.0, 1.0));
+                astar(&self.graph, &self.centers, from, to, scale)
+            }
+            SearchMode::Bidirectional => bidirectional_dijkstra(&self.graph, from, to),
+        };
+        check_cancel(&self.options)?;
+        let found = found.ok_or_else(|| PlanError::NoPath(self.no_path_reason(start, goal)))?;
+
+        Ok(self.finish(found, t, start_snap, goal_snap))
+    }
+
+    /// Like [`Planner::query`], treating the regions in `excluded` as impassable for
+    /// this query only, e.g. cells currently held by other agents. The graph is left
+    /// untouched. The start region is always usable; an excluded goal region gives
+    /// [`NoPathReason::Disconnected`]. Always searches with Dijkstra.
+    pub fn query_excluding(
+        &self,
+        start: (i32, i32),
+        goal: (i32, i32),
+        excluded: &HashSet<usize>,
+    ) -> Result<PlanResult, PlanError> {
+        let t = Instant::now();
+        let (from, to, start_snap, goal_snap) = self.prepare(start, goal)?;
+        let found = dijkstra_excluding(&self.graph, from, to, excluded)
+            .ok_or_else(|| PlanError::NoPath(self.no_path_reason(start, goal)))?;
+
+        Ok(self.finish(found, t, start_snap, goal_snap))
+    }
+
+    /// Like [`Planner::query`], confining the whole path to `allowed`, e.g. an
+    /// authorized operating zone: regions not fully inside it are pruned for this
+    /// query only, and the endpoints snap to the nearest region that is. Fails with
+    /// [`NoPathReason::OutsideArea`] if an endpoint lies outside `allowed`, and with
+    /// [`NoPathReason::Disconnected`] if the pruning separates them. Always searches
+    /// with Dijkstra.
+    pub fn query_within(&self, start: (i32, i32), goal: (i32, i32), allowed: &Shape) -> Result<PlanResult, PlanError> {
+        self.check_query(start, goal)?;
+        let outside = |(x, y): (i32, i32)| !shape_covers(allowed, x, y, 1);
+        if outside(start) || outside(goal) {
+            return Err(PlanError::NoPath(NoPathReason::OutsideArea));
+        }
+
+        let t = Instant::now();
+        let fits = |id: usize| {
+            let r = &self.regions[id];
+            shape_covers(allowed, r.x, r.y, r.w)
+        };
+        let inside: HashMap<usize, (f64, f64)> =
+            self.centers.iter().filter(|(&id, _)| fits(id)).map(|(&id, &c)| (id, c)).collect();
+        let excluded: HashSet<usize> = (0..self.regions.len()).filter(|&id| !fits(id)).collect();
+        if inside.is_empty() {
+            return Err(PlanError::NoPath(NoPathReason::Disconnected));
+        }
+        let (from, start_snap) = snap(start.0, start.1, &inside);
+        let (to, goal_snap) = snap(goal.0, goal.1, &inside);
+        let found = dijkstra_excluding(&self.graph, from, to, &excluded)
+            .ok_or_else(|| PlanError::NoPath(self.no_path_reason(start, goal)))?;
+
+        Ok(self.finish(found, t, start_snap, goal_snap))
+    }
+
+    /// Plans the route whose narrowest passage, the shortest border crossed between
+    /// two regions, is as wide as possible, e.g. for a wide load. Returns the plan,
+    /// whose cost is the ordinary graph cost of that route, and the bottleneck width.
+    /// Only edges between adjacent regions are used.
+    pub fn query_widest(&self, start: (i32, i32), goal: (i32, i32)) -> Result<(PlanResult, f64), PlanError> {
+        let t = Instant::now();
+        let (from, to, start_snap, goal_snap) = self.prepare(start, goal)?;
+        let weight = |a: usize, b: usize| self.graph[a].iter().find(|&&(to, _)| to == b).map(|&(_, w)| w);
+        let mut widths = border_width_graph(&self.regions);
+        for (a, edges) in widths.iter_mut().enumerate() {
+            edges.retain(|&(b, _)| weight(a, b).is_some());
+        }
+        let (path, width) = widest_path(&widths, from, to)
+            .ok_or_else(|| PlanError::NoPath(self.no_path_reason(start, goal)))?;
+
+        let found = SearchResult {
+            cost: path.windows(2).filter_map(|p| weight(p[0], p[1])).sum(),
+            expansions: 0,
+            path,
+        };
+        Ok((self.finish(found, t, start_snap, goal_snap), width))
+    }
+
+    /// Like [`Planner::query`], but the path may cover at most `cap` units of
+    /// distance inside penalty terrain, e.g. a vehicle that tolerates only so much
+    /// mud. As with edge costs, half of each edge counts as inside each endpoint's
+    /// region. Fails with [`NoPathReason::Disconnected`] when every route needs more.
+    /// Always searches with [`resource_constrained_path`].
+    pub fn query_terrain_capped(
+        &self,
+        start: (i32, i32),
+        goal: (i32, i32),
+        cap: f64,
+    ) -> Result<PlanResult, PlanError> {
+        let t = Instant::now();
+        let (from, to, start_snap, goal_snap) = self.prepare(start, goal)?;
+        let in_terrain: Vec<bool> = self
+            .regions
+            .iter()
+            .map(|r| self.terrain.iter().any(|t| intersects(&t.rect, r.x, r.y, r.w)))
+            .collect();
+        let usage: Vec<Vec<f64>> = self
+            .graph
+            .iter()
+            .enumerate()
+            .map(|(a, edges)| {
+                let (x1, y1) = self.centers[&a];
+                edges
+                    .iter()
+                    .map(|&(b, _)| {
+                        let (x2, y2) = self.centers[&b];
+                        let half = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt() / 2.0;
+                        half * (in_terrain[a] as u8 + in_terrain[b] as u8) as f64
+                    })
+                    .collect()
+            })
+            .collect();
+        let found = resource_constrained_path(&self.graph, &usage, from, to, cap)
+            .ok_or_else(|| PlanError::NoPath(self.no_path_reason(start, goal)))?;
+
+        Ok(self.finish(found, t, start_snap, goal_snap))
+    }
+
+    /// Plans around obstacles that move on a known schedule: each `(rect, from, until)`
+    /// closes the regions `rect` overlaps during steps `from..until`. Each step crosses
+    /// one edge or waits, and the earliest arrival within `horizon` steps wins (see
+    /// [`time_expanded_path`]); `path[t]` is the region occupied at step `t`. Fails
+    /// with [`NoPathReason::Disconnected`] when the goal cannot be reached in time.
+    pub fn query_timed(
+        &self,
+        start: (i32, i32),
+        goal: (i32, i32),
+        moving: &[(Rect, u32, u32)],
+        horizon: u32,
+    ) -> Result<PlanResult, PlanError> {
+        let t = Instant::now();
+        let (from, to, start_snap, goal_snap) = self.prepare(start, goal)?;
+        let closed: Vec<(usize, u32, u32)> = moving
+            .iter()
+            .flat_map(|&(rect, open, until)| {
+                let hit = self.regions.iter().enumerate().filter(move |(_, r)| intersects(&rect, r.x, r.y, r.w));
+                hit.map(move |(id, _)| (id, open, until))
+            })
+            .collect();
+        let found = time_expanded_path(&self.graph, &closed, from, to, horizon)
+            .ok_or_else(|| PlanError::NoPath(self.no_path_reason(start, goal)))?;
+
+        Ok(self.finish(found, t, start_snap, goal_snap))
+    }
+
+    /// Plans from `start` into any free region overlapping `goal`, e.g. a charging pad
+    /// spanning several cells, stopping at the cheapest one to reach. The region
+    /// reached is the last entry of [`PlanResult::path`]; `goal_snap` is 0. Always
+    /// searches with Dijkstra. Fails with [`NoPathReason::GoalBlocked`] if no free
+    /// region overlaps `goal`.
+    pub fn query_region(&self, start: (i32, i32), goal: Rect) -> Result<PlanResult, PlanError> {
+        self.check_bounds(start)?;
+        self.check_endpoint(start)?;
+
+        let t = Instant::now();
+        let (from, start_snap) = self.snap_endpoint(start);
+        let targets: Vec<usize> = (0..self.regions.len())
+            .filter(|&id| {
+                let r = &self.regions[id];
+                intersects(&goal, r.x, r.y, r.w)
+            })
+            .collect();
+        if targets.is_empty() {
+            return Err(PlanError::NoPath(NoPathReason::GoalBlocked));
+        }
+        let found = dijkstra_to_any(&self.graph, from, &targets).ok_or_else(|| {
+            let reason = if self.is_blocked(start) {
+                NoPathReason::StartBlocked
+            } else {
+                NoPathReason::Disconnected
+            };
+            PlanError::NoPath(reason)
+        })?;
+
+        Ok(self.finish(found, t, start_snap, 0.0))
+    }
+
+    /// Reuses `previous`, planned between the same `start` and `goal` before `changed`
+    /// was edited, when it is still valid: every region on it still exists and stays
+    /// clear of `changed`, consecutive regions are still linked, no waypoint leg
+    /// crosses `changed`, and the endpoints still snap to its ends. The reused result
+    /// has ids and cost re-read from the current graph and zero expansions. Otherwise
+    /// runs a fresh [`Planner::query`]. A reused path stays collision-free but may no
+    /// longer be the shortest once an obstacle has been removed.
+    pub fn replan(
+        &self,
+        previous: &PlanResult,
+        changed: Rect,
+        start: (i32, i32),
+        goal: (i32, i32),
+    ) -> Result<PlanResult, PlanError> {
+        match self.revalidate(previous, changed, start, goal) {
+            Some(result) => Ok(result),
+            None => self.query(start, goal),
+        }
+    }
+
+    fn revalidate(
+        &self,
+        previous: &PlanResult,
+        changed: Rect,
+        start: (i32, i32),
+        goal: (i32, i32),
+    ) -> Option<PlanResult> {
+        self.check_query(start, goal).ok()?;
+
+        let ids: HashMap<(i32, i32, i32, i32), usize> = self
+            .regions
+            .iter()
+            .enumerate()
+            .map(|(id, r)| ((r.x, r.y, r.w, r.h), id))
+            .collect();
+        let mut path = Vec::with_capacity(previous.regions.len());
+        for r in &previous.regions {
+            if intersects(&changed, r.x, r.y, r.w) {
+                return None;
+            }
+            path.push(*ids.get(&(r.x, r.y, r.w, r.h))?);
+        }
+
+        let mut cost = 0.0;
+        for pair in path.windows(2) {
+            let &(_, weight) = self.graph[pair[0]].iter().find(|&&(m, _)| m == pair[1])?;
+            cost += weight;
+        }
+        if previous.waypoints.windows(2).any(|leg| segment_intersects(&changed, leg[0], leg[1])) {
+            return None;
+        }
+        let (from, start_snap) = self.snap_endpoint(start);
+        let (to, goal_snap) = self.snap_endpoint(goal);
+        if path.first() != Some(&from) || path.last() != Some(&to) {
+            return None;
+        }
+
+        Some(PlanResult {
+            cost,
+            path,
+            waypoints: previous.waypoints.clone(),
+            start_snap,
+            goal_snap,
+            stats: self.stats.clone(),
+            regions: previous.regions.clone(),
+            n: self.n,
+            obstacles: self.obstacles.clone(),
+        })
+    }
+
+    /// Turns a search result into waypoints and stats per the planner's options.
+    fn finish(&self, found: SearchResult, t: Instant, start_snap: f64, goal_snap: f64) -> PlanResult {
+        let stats = PlanStats {
+            search_time: t.elapsed(),
+            expansions: found.expansions,
+            ..self.stats.clone()
+        };
+        log::debug!(
+            "found path of {} regions, cost {:.3}, {} expansions in {:?}",
+            found.path.len(),
+            found.cost,
+            found.expansions,
+            stats.search_time
+        );
+        let mut waypoints: Vec<_> = if self.options.medial_axis {
+            medial_waypoints(&found.path, &self.regions, &self.blocking, self.n)
+        } else if self.options.border_crossings {
+            border_waypoints(&found.path, &self.regions)
+        } else {
+            found.path.iter().map(|id| self.centers[id]).collect()
+        };
+        if let Some(bias) = self.options.center_bias {
+            waypoints = smooth_path(&waypoints, &self.blocking, bias);
+        }
+        if self.options.remove_loops {
+            waypoints = remove_loops(&waypoints);
+        }
+
+        PlanResult {
+            cost: found.cost,
+            regions: found.path.iter().map(|&id| self.regions[id]).collect(),
+            path: found.path,
+            waypoints,
+            start_snap,
+            goal_snap,
+            stats,
+            n: self.n,
+            obstacles: self.obstacles.clone(),
+        }
+    }
+
+    /// The obstacle containing the unit cell at `(x, y)`, if any.
+    pub fn obstacle_at(&self, x: i32, y: i32) -> Option<Rect> {
+        self.obstacles.iter().find(|obs| covers(obs, x, y, 1)).copied()
+    }
+
+    /// Returns true if the unit cell at `(x, y)` is inside an obstacle.
+    fn is_blocked(&self, (x, y): (i32, i32)) -> bool {
+        self.obstacle_at(x, y).is_some()
+    }
+
+    fn check_endpoint(&self, (x, y): (i32, i32)) -> Result<(), PlanError> {
+        if !self.options.reject_blocked_endpoints {
+            return Ok(());
+        }
+        match self.obstacle_at(x, y) {
+            Some(obstacle) => Err(PlanError::InsideObstacle { x, y, obstacle }),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that both query points lie on the grid and, with
+    /// [`PlanOptions::reject_blocked_endpoints`], outside obstacles.
+    fn check_query(&self, start: (i32, i32), goal: (i32, i32)) -> Result<(), PlanError> {
+        self.check_bounds(start)?;
+        self.check_bounds(goal)?;
+        self.check_endpoint(start)?;
+        self.check_endpoint(goal)
+    }
+
+    /// Checks both query points and snaps them to regions, returning
+    /// `(from, to, start_snap, goal_snap)`.
+    fn prepare(&self, start: (i32, i32), goal: (i32, i32)) -> Result<(usize, usize, f64, f64), PlanError> {
+        self.check_query(start, goal)?;
+        let (from, start_snap) = self.snap_endpoint(start);
+        let (to, goal_snap) = self.snap_endpoint(goal);
+        Ok((from, to, start_snap, goal_snap))
+    }
+
+    /// Snaps `p` to its nearest free region, returning the id and the distance to its
+    /// center. A region without edges, e.g. a free cell sealed in by obstacles, can
+    /// never be left, so the nearest region that has edges is used instead, with a
+    /// warning. Keeps the isolated region if no region has edges.
+    fn snap_endpoint(&self, p: (i32, i32)) -> (usize, f64) {
+        let (id, dist) = snap(p.0, p.1, &self.centers);
+        if !self.graph[id].is_empty() {
+            return (id, dist);
+        }
+        let nearest = find_k_nearest(p.0, p.1, &self.centers, self.centers.len());
+        match nearest.into_iter().find(|&other| !self.graph[other].is_empty()) {
+            Some(other) => {
+                log::warn!("({}, {}) snapped to isolated region {}, using region {} instead", p.0, p.1, id, other);
+                let (cx, cy) = self.centers[&other];
+                (other, ((p.0 as f64 - cx).powi(2) + (p.1 as f64 - cy).powi(2)).sqrt())
+            }
+            None => (id, dist),
+        }
+    }
+
+    /// Explains a failed search: a blocked endpoint may have been snapped into a
+    /// region cut off from the other one; otherwise the map is partitioned.
+    fn no_path_reason(&self, start: (i32, i32), goal: (i32, i32)) -> NoPathReason {
+        if self.is_blocked(start) {
+            NoPathReason::StartBlocked
+        } else if self.is_blocked(goal) {
+            NoPathReason::GoalBlocked
+        } else {
+            NoPathReason::Disconnected
+        }
+    }
+
+    fn check_bounds(&self, (x, y): (i32, i32)) -> Result<(), PlanError> {
+        if x < 0 || y < 0 || x >= self.n || y >= self.n {
+            return Err(PlanError::InvalidBounds { x, y, n: self.n });
+        }
+        Ok(())
+    }
+}
+
+/// Checksum tying a cached graph to its map and to the options that shape the graph:
+/// [`map_checksum`] extended with everything [`Planner::from_cache`] cannot redo,
+/// from quadrant order and inflation to edge weighting. Query-time options such as
+/// the search mode or smoothing are left out, so they may differ from the build.
+pub fn graph_checksum(n: i32, obstacles: &[Rect], terrain: &[Terrain], options: &PlanOptions) -> u64 {
+    let o = options;
+    // Debug formatting prints floats exactly, so equal settings give equal text
+    let settings = format!(
+        "{:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?}",
+        o.order,
+        o.merge_obstacles,
+        o.wrap,
+        o.look_radius,
+        o.merge_leaves,
+        o.heading_bias,
+        o.inflate,
+        o.size_penalty,
+        o.axis_scale,
+        o.max_degree,
+        o.center_push,
+        o.clearance_weighting,
+        o.adaptive_sides
+    );
+    extend_checksum(map_checksum(n, obstacles, terrain), settings.as_bytes())
+}
+
+/// Cell at the center of the single obstacle or terrain rectangle of `map` labeled
+/// `label`, for naming endpoints in scenario files ("from kitchen to door"). The
+/// center of a labeled obstacle is blocked, so a query from it snaps to the nearest
+/// free region. Fails with [`PlanError::UnknownLabel`] or [`PlanError::AmbiguousLabel`]
+/// unless exactly one entry matches.
+pub fn label_center(map: &Map, label: &str) -> Result<(i32, i32), PlanError> {
+    let obstacles = map.obstacles.iter().zip(&map.obstacle_labels);
+    let terrain = map.terrain.iter().map(|t| &t.rect).zip(&map.terrain_labels);
+    let matches: Vec<&Rect> = obstacles
+        .chain(terrain)
+        .filter(|(_, l)| l.as_deref() == Some(label))
+        .map(|(r, _)| r)
+        .collect();
+    match matches.as_slice() {
+        [r] => Ok((r.x + r.w / 2, r.y + r.h / 2)),
+        [] => Err(PlanError::UnknownLabel(label.to_string())),
+        _ => Err(PlanError::AmbiguousLabel {
+            label: label.to_string(),
+            count: matches.len(),
+        }),
+    }
+}
+
+/// Reads the map at `path`, builds the planner and answers a single query.
+/// Never panics: every failure is reported through [`PlanError`].
+pub fn plan_from_file(
+    path: &str,
+    start: (i32, i32),
+    goal: (i32, i32),
+    options: &PlanOptions,
+) -> Result<PlanResult, PlanError> {
+    let map = read_input(path)?;
+    Planner::with_terrain(map.n, &map.obstacles, &map.terrain, options)?.query(start, goal)
+}
+
+/// Like [`plan_from_file`], but gives up with [`PlanError::Timeout`] once `timeout`
+/// has elapsed.
+///
+/// The work runs on a worker thread. On success the worker is joined before returning.
+/// On timeout the call returns immediately and raises the worker's
+/// [`PlanOptions::cancel`] flag, replacing any flag in `options`. The worker polls it
+/// at every node of the tree build, between graph construction steps and at every
+/// expansion of a [`SearchMode::Dijkstra`] search, so it exits shortly after the
+/// deadline without producing a result. Parsing the file and the other search modes
+/// are not interrupted and run to completion first.
+pub fn plan_with_timeout(
+    path: &str,
+    start: (i32, i32),
+    goal: (i32, i32),
+    options: &PlanOptions,
+    timeout: Duration,
+) -> Result<PlanResult, PlanError> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = mpsc::channel();
+
+    let worker = {
+        let cancel = Arc::clone(&cancel);
+        let path = path.to_string();
+        let options = PlanOptions { cancel: Some(cancel), ..options.clone() };
+        thread::spawn(move || {
+            let result = (|| {
+                let map = read_input(&path)?;
+                check_cancel(&options)?;
+                Planner::with_terrain(map.n, &map.obstacles, &map.terrain, &options)?.query(start, goal)
+            })();
+            // The receiver is gone if the caller already timed out
+            let _ = tx.send(result);
+        })
+    };
+
+    match rx.recv_timeout(timeout) {
+        Ok(result) => {
+            let _ = worker.join();
+            result
+        }
+        Err(_) => {
+            cancel.store(true, Ordering::Relaxed);
+            Err(PlanError::Timeout)
+        }
+    }
+}
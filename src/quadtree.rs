@@ -1,7 +1,10 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 use crate::models::rect::Rect;
-use crate::models::quad::Quad;
-use crate::geometry::{covers, intersects};
+use crate::models::quad::{Quad, QuadrantOrder};
+use crate::models::shape::Shape;
+use crate::models::terrain::Terrain;
+use crate::geometry::{covers, intersects, segment_intersects, shape_covers, shape_intersects, shared_border};
 
 /// Recursively builds a quadtree over the grid, marking cells as Free, Blocked, or Split.
 pub fn build_quad(obstacles: &[Rect], x: i32, y: i32, size: i32) -> Quad {
@@ -14,29 +17,381 @@ pub fn build_quad(obstacles: &[Rect], x: i32, y: i32, size: i32) -> Quad {
         return Quad::Free(x, y, size);
     }
 
+    // Only obstacles touching this cell can affect it or its children, so the
+    // working set shrinks with depth
+    let local: Vec<Rect> = obstacles
+        .iter()
+        .filter(|obs| intersects(obs, x, y, size))
+        .copied()
+        .collect();
+
+    // If no obstacle even touches this cell, it's entirely free
+    if local.is_empty() {
+        return Quad::Free(x, y, size);
+    }
+
     // If any obstacle fully covers this cell, it's entirely blocked
-    if obstacles.iter().any(|obs| covers(obs, x, y, size)) {
+    if local.iter().any(|obs| covers(obs, x, y, size)) {
         return Quad::Blocked;
     }
 
-    // If no obstacle even touches this cell, it's entirely free
-    if !obstacles.iter().any(|obs| intersects(obs, x, y, size)) {
+    // Otherwise split into 4 children: NW, NE, SW, SE
+    let h = size / 2;
+    let nw = build_quad(&local, x, y + h, h);
+    let ne = build_quad(&local, x + h, y + h, h);
+    let sw = build_quad(&local, x, y, h);
+    let se = build_quad(&local, x + h, y, h);
+
+    Quad::Split(Box::new([nw, ne, sw, se]))
+}
+
+/// Like [`build_quad`], but stops splitting at `min_size`: a cell of that size only
+/// partly covered by obstacles becomes a `Free` leaf. The tree over-approximates free
+/// space, so it is only suitable for coarse routing that is refined afterwards.
+pub fn build_quad_coarse(obstacles: &[Rect], x: i32, y: i32, size: i32, min_size: i32) -> Quad {
+    let local: Vec<Rect> = obstacles
+        .iter()
+        .filter(|obs| intersects(obs, x, y, size))
+        .copied()
+        .collect();
+
+    if local.is_empty() {
+        return Quad::Free(x, y, size);
+    }
+    if local.iter().any(|obs| covers(obs, x, y, size)) {
+        return Quad::Blocked;
+    }
+    if size <= min_size.max(1) {
         return Quad::Free(x, y, size);
     }
 
-    // Otherwise split into 4 children: NW, NE, SW, SE
     let h = size / 2;
-    let nw = build_quad(obstacles, x, y + h, h);
-    let ne = build_quad(obstacles, x + h, y + h, h);
-    let sw = build_quad(obstacles, x, y, h);
-    let se = build_quad(obstacles, x + h, y, h);
+    let nw = build_quad_coarse(&local, x, y + h, h, min_size);
+    let ne = build_quad_coarse(&local, x + h, y + h, h, min_size);
+    let sw = build_quad_coarse(&local, x, y, h, min_size);
+    let se = build_quad_coarse(&local, x + h, y, h, min_size);
 
     Quad::Split(Box::new([nw, ne, sw, se]))
 }
 
+/// Like [`build_quad`], with a resolution that follows obstacle density: a partly
+/// blocked cell is not split below `min_side(k)`, `k` being the number of obstacles
+/// touching it, and is marked blocked instead. Open areas stay coarse while clutter
+/// keeps fine cells. Conservative: free space is only ever lost, never invented.
+pub fn build_quad_adaptive(
+    obstacles: &[Rect],
+    x: i32,
+    y: i32,
+    size: i32,
+    min_side: &impl Fn(usize) -> i32,
+) -> Quad {
+    build_quad_adaptive_until(obstacles, x, y, size, min_side, &|| false).unwrap()
+}
+
+/// Like [`build_quad_adaptive`], polling `stop` at every node and giving up with
+/// `None` as soon as it returns true, e.g. when a deadline has passed.
+pub fn build_quad_adaptive_until(
+    obstacles: &[Rect],
+    x: i32,
+    y: i32,
+    size: i32,
+    min_side: &impl Fn(usize) -> i32,
+    stop: &impl Fn() -> bool,
+) -> Option<Quad> {
+    if stop() {
+        return None;
+    }
+    let local: Vec<Rect> = obstacles
+        .iter()
+        .filter(|obs| intersects(obs, x, y, size))
+        .copied()
+        .collect();
+
+    if local.is_empty() {
+        return Some(Quad::Free(x, y, size));
+    }
+    if size <= min_side(local.len()).max(1) || local.iter().any(|obs| covers(obs, x, y, size)) {
+        return Some(Quad::Blocked);
+    }
+
+    let h = size / 2;
+    let nw = build_quad_adaptive_until(&local, x, y + h, h, min_side, stop)?;
+    let ne = build_quad_adaptive_until(&local, x + h, y + h, h, min_side, stop)?;
+    let sw = build_quad_adaptive_until(&local, x, y, h, min_side, stop)?;
+    let se = build_quad_adaptive_until(&local, x + h, y, h, min_side, stop)?;
+
+    Some(Quad::Split(Box::new([nw, ne, sw, se])))
+}
+
+/// Density-to-resolution table for [`build_quad_adaptive`]: each `(count, side)` entry
+/// lets cells touched by at least `count` obstacles be split down to `side`. The
+/// smallest side among the matching entries wins; with none, cells go down to 1.
+pub fn density_min_side(table: &[(usize, i32)], obstacles: usize) -> i32 {
+    table
+        .iter()
+        .filter(|&&(count, _)| obstacles >= count)
+        .map(|&(_, side)| side)
+        .min()
+        .unwrap_or(1)
+}
+
+/// Largest grid side [`build_quad_auto`] builds recursively by default: 2^14.
+///
+/// In the `build_quad_by_size` benchmark (benches/build.rs, 2n scattered obstacles)
+/// the recursive build beat [`build_quad_iterative`] at every size measured:
+///
+/// | n     | recursive | iterative |
+/// |-------|-----------|-----------|
+/// | 64    | 98 µs     | 161 µs    |
+/// | 256   | 780 µs    | 1.14 ms   |
+/// | 1024  | 3.50 ms   | 5.50 ms   |
+/// | 4096  | 18.4 ms   | 28.2 ms   |
+/// | 16384 | 90.0 ms   | 125 ms    |
+///
+/// There is no crossover to pick, so the limit is the largest size where recursion
+/// was measured to win; beyond it the iterative build trades speed for a stack that
+/// does not grow with depth.
+pub const RECURSIVE_BUILD_LIMIT: i32 = 1 << 14;
+
+/// Builds with [`build_quad`] when `size <= recursive_limit` and with
+/// [`build_quad_iterative`] above it. Both produce the same tree; a smaller limit
+/// suits threads with small stacks.
+pub fn build_quad_auto(obstacles: &[Rect], x: i32, y: i32, size: i32, recursive_limit: i32) -> Quad {
+    if size <= recursive_limit {
+        build_quad(obstacles, x, y, size)
+    } else {
+        build_quad_iterative(obstacles, x, y, size)
+    }
+}
+
+/// Builds a quadtree over just `window`, treating everything outside it as blocked.
+/// The root is the smallest power-of-two square anchored at the window's
+/// bottom-left corner that contains it; the part of that square beyond the window
+/// comes out `Blocked`. Only obstacles touching the window are considered.
+pub fn build_quad_window(obstacles: &[Rect], window: Rect) -> Quad {
+    let side = (window.w.max(window.h).max(1) as u32).next_power_of_two() as i32;
+    let mut local: Vec<Rect> = obstacles
+        .iter()
+        .filter(|obs| {
+            obs.x < window.x + window.w
+                && window.x < obs.x + obs.w
+                && obs.y < window.y + window.h
+                && window.y < obs.y + obs.h
+        })
+        .copied()
+        .collect();
+
+    // Padding between the window and the power-of-two root
+    local.push(Rect { x: window.x + window.w, y: window.y, w: side - window.w, h: side });
+    local.push(Rect { x: window.x, y: window.y + window.h, w: window.w, h: side - window.h });
+    local.retain(|r| r.w > 0 && r.h > 0);
+
+    build_quad(&local, window.x, window.y, side)
+}
+
+/// Inverse of [`build_quad`]: the rectangles describe the *free* space and everything
+/// else is blocked. A cell is `Free` only when one free rectangle covers it,
+/// `Blocked` when none touches it, and split otherwise.
+pub fn build_quad_from_free(free_rects: &[Rect], x: i32, y: i32, size: i32) -> Quad {
+    if size <= 1 {
+        if free_rects.iter().any(|r| intersects(r, x, y, size)) {
+            return Quad::Free(x, y, size);
+        }
+        return Quad::Blocked;
+    }
+
+    if free_rects.iter().any(|r| covers(r, x, y, size)) {
+        return Quad::Free(x, y, size);
+    }
+
+    if !free_rects.iter().any(|r| intersects(r, x, y, size)) {
+        return Quad::Blocked;
+    }
+
+    let h = size / 2;
+    let nw = build_quad_from_free(free_rects, x, y + h, h);
+    let ne = build_quad_from_free(free_rects, x + h, y + h, h);
+    let sw = build_quad_from_free(free_rects, x, y, h);
+    let se = build_quad_from_free(free_rects, x + h, y, h);
+
+    Quad::Split(Box::new([nw, ne, sw, se]))
+}
+
+/// Collapses, bottom-up, every `Split` whose four children are all `Free` into one
+/// `Free` leaf covering the parent. This happens e.g. in [`build_quad_from_free`]
+/// when several free rectangles together cover a cell that none covers alone.
+pub fn merge_free_leaves(quad: Quad) -> Quad {
+    match quad {
+        Quad::Split(children) => {
+            let [nw, ne, sw, se] = *children;
+            let children = [nw, ne, sw, se].map(merge_free_leaves);
+            match &children {
+                [Quad::Free(_, _, s), Quad::Free(..), Quad::Free(x, y, _), Quad::Free(..)] => {
+                    Quad::Free(*x, *y, 2 * s)
+                }
+                _ => Quad::Split(Box::new(children)),
+            }
+        }
+        leaf => leaf,
+    }
+}
+
+/// Like [`build_quad`], for composite obstacles such as hollow rectangles.
+/// Free space inside a shape (e.g. the inside of a ring) is left `Free`.
+pub fn build_shape_quad(shapes: &[Shape], x: i32, y: i32, size: i32) -> Quad {
+    if size <= 1 {
+        if shapes.iter().any(|s| shape_intersects(s, x, y, size)) {
+            return Quad::Blocked;
+        }
+        return Quad::Free(x, y, size);
+    }
+
+    if shapes.iter().any(|s| shape_covers(s, x, y, size)) {
+        return Quad::Blocked;
+    }
+
+    if !shapes.iter().any(|s| shape_intersects(s, x, y, size)) {
+        return Quad::Free(x, y, size);
+    }
+
+    let h = size / 2;
+    let nw = build_shape_quad(shapes, x, y + h, h);
+    let ne = build_shape_quad(shapes, x + h, y + h, h);
+    let sw = build_shape_quad(shapes, x, y, h);
+    let se = build_shape_quad(shapes, x + h, y, h);
+
+    Quad::Split(Box::new([nw, ne, sw, se]))
+}
+
+/// Like [`build_quad`], but also splits cells that are only partly covered by a
+/// penalty `terrain` rectangle, so every free leaf has a uniform traversal cost.
+pub fn build_terrain_quad(obstacles: &[Rect], terrain: &[Terrain], x: i32, y: i32, size: i32) -> Quad {
+    build_terrain_quad_until(obstacles, terrain, x, y, size, &|| false).unwrap()
+}
+
+/// Like [`build_terrain_quad`], polling `stop` at every node and giving up with
+/// `None` as soon as it returns true, e.g. when a deadline has passed.
+pub fn build_terrain_quad_until(
+    obstacles: &[Rect],
+    terrain: &[Terrain],
+    x: i32,
+    y: i32,
+    size: i32,
+    stop: &impl Fn() -> bool,
+) -> Option<Quad> {
+    if stop() {
+        return None;
+    }
+    if size <= 1 {
+        return Some(build_quad(obstacles, x, y, size));
+    }
+
+    // As in `build_quad`, children only need what touches this cell
+    let local: Vec<Rect> = obstacles
+        .iter()
+        .filter(|obs| intersects(obs, x, y, size))
+        .copied()
+        .collect();
+    if local.iter().any(|obs| covers(obs, x, y, size)) {
+        return Some(Quad::Blocked);
+    }
+
+    let cells: Vec<Terrain> = terrain
+        .iter()
+        .filter(|t| intersects(&t.rect, x, y, size))
+        .copied()
+        .collect();
+    let mixed = cells.iter().any(|t| !covers(&t.rect, x, y, size));
+    if local.is_empty() && !mixed {
+        return Some(Quad::Free(x, y, size));
+    }
+
+    let h = size / 2;
+    let nw = build_terrain_quad_until(&local, &cells, x, y + h, h, stop)?;
+    let ne = build_terrain_quad_until(&local, &cells, x + h, y + h, h, stop)?;
+    let sw = build_terrain_quad_until(&local, &cells, x, y, h, stop)?;
+    let se = build_terrain_quad_until(&local, &cells, x + h, y, h, stop)?;
+
+    Some(Quad::Split(Box::new([nw, ne, sw, se])))
+}
+
+/// Cost multiplier of each free region: the highest `cost` among the terrain
+/// rectangles overlapping it, or `1.0` outside any terrain.
+pub fn terrain_costs(regions: &[Rect], terrain: &[Terrain]) -> Vec<f64> {
+    regions
+        .iter()
+        .map(|r| {
+            terrain
+                .iter()
+                .filter(|t| intersects(&t.rect, r.x, r.y, r.w))
+                .map(|t| t.cost)
+                .reduce(f64::max)
+                .unwrap_or(1.0)
+        })
+        .collect()
+}
+
+/// Explicit-stack version of [`build_quad`]: produces the same tree without recursing,
+/// so very deep subdivisions cannot overflow the call stack.
+pub fn build_quad_iterative(obstacles: &[Rect], x: i32, y: i32, size: i32) -> Quad {
+    enum Task {
+        // Cell plus the obstacles intersecting its parent, shared by the four siblings
+        Build(i32, i32, i32, Rc<Vec<Rect>>),
+        Assemble,
+    }
+
+    let mut tasks = vec![Task::Build(x, y, size, Rc::new(obstacles.to_vec()))];
+    let mut done: Vec<Quad> = Vec::new();
+
+    while let Some(task) = tasks.pop() {
+        match task {
+            Task::Build(x, y, size, candidates) => {
+                let local: Vec<Rect> = candidates
+                    .iter()
+                    .filter(|obs| intersects(obs, x, y, size))
+                    .copied()
+                    .collect();
+                if local.is_empty() {
+                    done.push(Quad::Free(x, y, size));
+                } else if size <= 1 || local.iter().any(|obs| covers(obs, x, y, size)) {
+                    done.push(Quad::Blocked);
+                } else {
+                    // Children are pushed in reverse so they complete in NW, NE, SW, SE order
+                    let h = size / 2;
+                    let local = Rc::new(local);
+                    tasks.push(Task::Assemble);
+                    tasks.push(Task::Build(x + h, y, h, Rc::clone(&local)));
+                    tasks.push(Task::Build(x, y, h, Rc::clone(&local)));
+                    tasks.push(Task::Build(x + h, y + h, h, Rc::clone(&local)));
+                    tasks.push(Task::Build(x, y + h, h, local));
+                }
+            }
+            Task::Assemble => {
+                let se = done.pop().unwrap();
+                let sw = done.pop().unwrap();
+                let ne = done.pop().unwrap();
+                let nw = done.pop().unwrap();
+                done.push(Quad::Split(Box::new([nw, ne, sw, se])));
+            }
+        }
+    }
+
+    done.pop().unwrap()
+}
+
 /// Traverses the quadtree and collects the center coordinates of all free cells,
 /// assigning each a unique id.
 pub fn collect_free(quad: &Quad, id: &mut usize, map: &mut HashMap<usize, (f64, f64)>) {
+    collect_free_ordered(quad, QuadrantOrder::default(), id, map);
+}
+
+/// Same as [`collect_free`], visiting children in `order`.
+pub fn collect_free_ordered(
+    quad: &Quad,
+    order: QuadrantOrder,
+    id: &mut usize,
+    map: &mut HashMap<usize, (f64, f64)>,
+) {
     match quad {
         Quad::Free(x, y, s) => {
             let cx = *x as f64 + *s as f64 / 2.0;
@@ -45,10 +400,349 @@ pub fn collect_free(quad: &Quad, id: &mut usize, map: &mut HashMap<usize, (f64,
             *id += 1;
         }
         Quad::Split(children) => {
-            for child in children.iter() {
-                collect_free(child, id, map);
+            for i in order.indices() {
+                collect_free_ordered(&children[i], order, id, map);
             }
         }
         Quad::Blocked => {}
     }
 }
+
+/// Returns the rectangle of every free leaf, indexed by the ids [`collect_free`] assigns.
+pub fn free_leaves(quad: &Quad) -> Vec<Rect> {
+    free_leaves_ordered(quad, QuadrantOrder::default())
+}
+
+/// Same as [`free_leaves`], indexed by the ids [`collect_free_ordered`] assigns for `order`.
+pub fn free_leaves_ordered(quad: &Quad, order: QuadrantOrder) -> Vec<Rect> {
+    fn walk(quad: &Quad, order: QuadrantOrder, out: &mut Vec<Rect>) {
+        match quad {
+            Quad::Free(x, y, s) => out.push(Rect { x: *x, y: *y, w: *s, h: *s }),
+            Quad::Split(children) => {
+                for i in order.indices() {
+                    walk(&children[i], order, out);
+                }
+            }
+            Quad::Blocked => {}
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(quad, order, &mut out);
+    out
+}
+
+/// Checks that `quad` is a well-formed decomposition of the `n×n` grid rooted at
+/// (0, 0): every split halves an even-sized cell so its children tile it exactly,
+/// every free leaf sits at the cell its position in the tree implies, and the
+/// leaves together cover `n×n` without overlap.
+pub fn validate(quad: &Quad, n: i32) -> Result<(), String> {
+    fn check(quad: &Quad, x: i32, y: i32, size: i32, area: &mut i64) -> Result<(), String> {
+        match quad {
+            Quad::Free(fx, fy, fs) => {
+                if (*fx, *fy, *fs) != (x, y, size) {
+                    return Err(format!(
+                        "free leaf ({}, {}, {}) is stored in cell ({}, {}, {})",
+                        fx, fy, fs, x, y, size
+                    ));
+                }
+                *area += size as i64 * size as i64;
+            }
+            Quad::Blocked => *area += size as i64 * size as i64,
+            Quad::Split(children) => {
+                if size < 2 || size % 2 != 0 {
+                    return Err(format!(
+                        "cell ({}, {}, {}) cannot be split into four equal quadrants",
+                        x, y, size
+                    ));
+                }
+                let h = size / 2;
+                let cells = [(x, y + h), (x + h, y + h), (x, y), (x + h, y)];
+                for (child, (cx, cy)) in children.iter().zip(cells) {
+                    check(child, cx, cy, h, area)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    if n <= 0 {
+        return Err(format!("grid size {} is not positive", n));
+    }
+    let mut area = 0;
+    check(quad, 0, 0, n, &mut area)?;
+    let expected = n as i64 * n as i64;
+    if area != expected {
+        return Err(format!("leaves cover {} cells, expected {}", area, expected));
+    }
+    Ok(())
+}
+
+/// Extent `(x, y, size)` of the root of `quad`, recovered from any free leaf and
+/// its position in the tree. `None` if the tree has no free leaf.
+fn quad_extent(quad: &Quad) -> Option<(i32, i32, i32)> {
+    match quad {
+        Quad::Free(x, y, s) => Some((*x, *y, *s)),
+        Quad::Blocked => None,
+        Quad::Split(children) => children.iter().enumerate().find_map(|(i, child)| {
+            let (x, y, s) = quad_extent(child)?;
+            let east = i == 1 || i == 3;
+            let north = i < 2;
+            Some((x - if east { s } else { 0 }, y - if north { s } else { 0 }, 2 * s))
+        }),
+    }
+}
+
+/// Free leaf containing the point `p`, or `None` if it is blocked or outside `extent`.
+fn leaf_at(quad: &Quad, (x, y, size): (i32, i32, i32), p: (f64, f64)) -> Option<Rect> {
+    let (fx, fy) = (x as f64, y as f64);
+    if p.0 < fx || p.1 < fy || p.0 >= fx + size as f64 || p.1 >= fy + size as f64 {
+        return None;
+    }
+    match quad {
+        Quad::Free(x, y, s) => Some(Rect { x: *x, y: *y, w: *s, h: *s }),
+        Quad::Blocked => None,
+        Quad::Split(children) => {
+            let h = size / 2;
+            let east = p.0 >= (x + h) as f64;
+            let north = p.1 >= (y + h) as f64;
+            let (i, cx, cy) = match (north, east) {
+                (true, false) => (0, x, y + h),
+                (true, true) => (1, x + h, y + h),
+                (false, false) => (2, x, y),
+                (false, true) => (3, x + h, y),
+            };
+            leaf_at(&children[i], (cx, cy, h), p)
+        }
+    }
+}
+
+/// Casts a ray from `origin` along `dir` and returns the first point where it leaves
+/// free space, on the border of a blocked leaf or of the grid. Walks leaf to leaf,
+/// descending the tree at each step. `None` if `origin` is not in free space or
+/// `dir` is zero.
+pub fn raycast(quad: &Quad, origin: (f64, f64), dir: (f64, f64)) -> Option<(f64, f64)> {
+    let len = (dir.0 * dir.0 + dir.1 * dir.1).sqrt();
+    if len == 0.0 {
+        return None;
+    }
+    let (dx, dy) = (dir.0 / len, dir.1 / len);
+    let extent = quad_extent(quad)?;
+    leaf_at(quad, extent, origin)?;
+
+    // Nudge sample points along the ray so cell borders resolve to the next cell
+    const EPS: f64 = 1e-9;
+    let mut t = 0.0;
+    loop {
+        let p = (origin.0 + dx * t, origin.1 + dy * t);
+        let Some(cell) = leaf_at(quad, extent, (p.0 + dx * EPS, p.1 + dy * EPS)) else {
+            return Some(p);
+        };
+        // Distance along the ray to the far side of this cell on each axis
+        let exit = |pos: f64, d: f64, lo: i32, len: i32| {
+            if d > 0.0 {
+                ((lo + len) as f64 - pos) / d
+            } else if d < 0.0 {
+                (lo as f64 - pos) / d
+            } else {
+                f64::INFINITY
+            }
+        };
+        let step = exit(p.0, dx, cell.x, cell.w).min(exit(p.1, dy, cell.y, cell.h));
+        t += step.max(EPS);
+    }
+}
+
+/// Returns true if the segment `a`–`b` lies in free space: inside the grid and
+/// through the interior of no blocked leaf. Only the subtrees the segment passes
+/// through are visited, so the cost follows the number of leaves crossed rather
+/// than the number of obstacles. As with [`segment_intersects`], running along the
+/// border of a blocked leaf does not count as crossing it.
+pub fn segment_is_free(quad: &Quad, a: (f64, f64), b: (f64, f64)) -> bool {
+    let Some((x, y, size)) = quad_extent(quad) else {
+        return false;
+    };
+    let (lo, hi) = ((x as f64, y as f64), ((x + size) as f64, (y + size) as f64));
+    let inside = |p: (f64, f64)| p.0 >= lo.0 && p.1 >= lo.1 && p.0 <= hi.0 && p.1 <= hi.1;
+    inside(a) && inside(b) && segment_clear(quad, (x, y, size), a, b)
+}
+
+fn segment_clear(quad: &Quad, (x, y, size): (i32, i32, i32), a: (f64, f64), b: (f64, f64)) -> bool {
+    if !segment_intersects(&Rect { x, y, w: size, h: size }, a, b) {
+        return true;
+    }
+    match quad {
+        Quad::Free(..) => true,
+        Quad::Blocked => false,
+        Quad::Split(children) => {
+            let h = size / 2;
+            let origins = [(x, y + h), (x + h, y + h), (x, y), (x + h, y)];
+            children
+                .iter()
+                .zip(origins)
+                .all(|(child, (cx, cy))| segment_clear(child, (cx, cy, h), a, b))
+        }
+    }
+}
+
+/// Morton (Z-order) code of the cell at `(x, y)`: the bits of `x` and `y`
+/// interleaved, `x` in the even positions.
+pub fn morton_code(x: u32, y: u32) -> u64 {
+    let spread = |v: u32| {
+        let mut v = v as u64;
+        v = (v | (v << 16)) & 0x0000_ffff_0000_ffff;
+        v = (v | (v << 8)) & 0x00ff_00ff_00ff_00ff;
+        v = (v | (v << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+        v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+        (v | (v << 1)) & 0x5555_5555_5555_5555
+    };
+    spread(x) | (spread(y) << 1)
+}
+
+/// Free leaves tagged with the Morton code of their lower-left corner, relative to
+/// the corner of the tree, sorted by Z-order; each leaf of side `s` covers the codes
+/// `code..code + s * s`. Blocked cells leave holes in the code sequence, so
+/// consecutive entries need not be contiguous.
+pub fn morton_leaves(quad: &Quad) -> Vec<(u64, Rect)> {
+    let Some((x0, y0, _)) = quad_extent(quad) else {
+        return Vec::new();
+    };
+    let mut cells: Vec<(u64, Rect)> = free_leaves(quad)
+        .into_iter()
+        .map(|r| (morton_code((r.x - x0) as u32, (r.y - y0) as u32), r))
+        .collect();
+    cells.sort_by_key(|&(code, _)| code);
+    cells
+}
+
+/// Free leaf containing the unit cell at `(x, y)`, or `None` if that cell is
+/// blocked or outside the tree.
+pub fn leaf_at_point(quad: &Quad, x: i32, y: i32) -> Option<Rect> {
+    let extent = quad_extent(quad)?;
+    leaf_at(quad, extent, (x as f64 + 0.5, y as f64 + 0.5))
+}
+
+/// Free leaves sharing a stretch of border with `leaf`, found by descending only
+/// into the nodes that touch it. The neighbor query behind graph-free searches
+/// such as [`dijkstra_lazy`](crate::algo::dijkstra_lazy).
+pub fn leaf_neighbors(quad: &Quad, leaf: &Rect) -> Vec<Rect> {
+    let mut out = Vec::new();
+    if let Some(extent) = quad_extent(quad) {
+        touching_leaves(quad, extent, leaf, &mut out);
+    }
+    out
+}
+
+fn touching_leaves(quad: &Quad, (x, y, size): (i32, i32, i32), leaf: &Rect, out: &mut Vec<Rect>) {
+    let touches = x <= leaf.x + leaf.w && leaf.x <= x + size && y <= leaf.y + leaf.h && leaf.y <= y + size;
+    if !touches {
+        return;
+    }
+    match quad {
+        Quad::Free(..) => {
+            let r = Rect { x, y, w: size, h: size };
+            if shared_border(leaf, &r).is_some() {
+                out.push(r);
+            }
+        }
+        Quad::Blocked => {}
+        Quad::Split(children) => {
+            let h = size / 2;
+            let origins = [(x, y + h), (x + h, y + h), (x, y), (x + h, y)];
+            for (child, (cx, cy)) in children.iter().zip(origins) {
+                touching_leaves(child, (cx, cy, h), leaf, out);
+            }
+        }
+    }
+}
+
+/// Smallest node of `quad`, the tree of the square `(x, y, size)`, whose extent fully
+/// contains `rect`, with that extent. Coordinates are tracked while descending, so
+/// blocked subtrees are found as well as free ones. After an edit confined to `rect`,
+/// replacing the node by a rebuild of its extent (e.g. with [`build_quad`]) updates
+/// the tree without touching the rest. `None` if `rect` reaches outside the square.
+pub fn smallest_enclosing_node_mut<'a>(
+    quad: &'a mut Quad,
+    x: i32,
+    y: i32,
+    size: i32,
+    rect: &Rect,
+) -> Option<(&'a mut Quad, Rect)> {
+    if !contains_rect((x, y, size), rect) {
+        return None;
+    }
+    Some(enclosing(quad, (x, y, size), rect))
+}
+
+/// Whether the square `(x, y, size)` contains all of `rect`.
+fn contains_rect((x, y, size): (i32, i32, i32), rect: &Rect) -> bool {
+    rect.x >= x && rect.y >= y && rect.x + rect.w <= x + size && rect.y + rect.h <= y + size
+}
+
+fn enclosing<'a>(quad: &'a mut Quad, (x, y, size): (i32, i32, i32), rect: &Rect) -> (&'a mut Quad, Rect) {
+    let h = size / 2;
+    let origins = [(x, y + h), (x + h, y + h), (x, y), (x + h, y)];
+    let inside = origins.iter().position(|&(cx, cy)| contains_rect((cx, cy, h), rect));
+    match (quad, inside) {
+        (Quad::Split(children), Some(i)) => {
+            let (cx, cy) = origins[i];
+            enclosing(&mut children[i], (cx, cy, h), rect)
+        }
+        (quad, _) => (quad, Rect { x, y, w: size, h: size }),
+    }
+}
+
+/// How a node of the grid differs between two trees, see [`quad_diff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// A blocked leaf, or blocked part of a leaf, is now free.
+    BecameFree,
+    /// A free leaf, or free part of a leaf, is now blocked.
+    BecameBlocked,
+    /// A leaf is now split into smaller nodes.
+    Subdivided,
+    /// A split node is now a single leaf.
+    Merged,
+}
+
+/// Differences between two trees over the same grid, in pre-order (NW, NE, SW, SE).
+/// A node whose structure changed is reported as [`Change::Subdivided`] or
+/// [`Change::Merged`], followed by the classification changes inside it, so the
+/// `BecameFree` and `BecameBlocked` rectangles together are exactly the cells that
+/// changed state.
+pub fn quad_diff(old: &Quad, new: &Quad) -> Vec<(Rect, Change)> {
+    let mut out = Vec::new();
+    if let Some(extent) = quad_extent(old).or_else(|| quad_extent(new)) {
+        diff_nodes(old, new, extent, &mut out);
+    }
+    out
+}
+
+fn diff_nodes(old: &Quad, new: &Quad, (x, y, size): (i32, i32, i32), out: &mut Vec<(Rect, Change)>) {
+    let rect = Rect { x, y, w: size, h: size };
+    let h = size / 2;
+    let origins = [(x, y + h), (x + h, y + h), (x, y), (x + h, y)];
+    // A leaf facing a split node is compared, unchanged, against each of its children
+    match (old, new) {
+        (Quad::Split(a), Quad::Split(b)) => {
+            for i in 0..4 {
+                diff_nodes(&a[i], &b[i], (origins[i].0, origins[i].1, h), out);
+            }
+        }
+        (Quad::Split(a), leaf) => {
+            out.push((rect, Change::Merged));
+            for i in 0..4 {
+                diff_nodes(&a[i], leaf, (origins[i].0, origins[i].1, h), out);
+            }
+        }
+        (leaf, Quad::Split(b)) => {
+            out.push((rect, Change::Subdivided));
+            for i in 0..4 {
+                diff_nodes(leaf, &b[i], (origins[i].0, origins[i].1, h), out);
+            }
+        }
+        (Quad::Free(..), Quad::Blocked) => out.push((rect, Change::BecameBlocked)),
+        (Quad::Blocked, Quad::Free(..)) => out.push((rect, Change::BecameFree)),
+        _ => {}
+    }
+}
@@ -0,0 +1,48 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+
+use png::{BitDepth, ColorType, Encoder};
+
+use crate::planner::Planner;
+
+const FREE: [u8; 3] = [255, 255, 255];
+const BLOCKED: [u8; 3] = [0x33, 0x33, 0x33];
+const PATH: [u8; 3] = [255, 0, 0];
+
+/// Writes the planner's map to `path` as an RGB PNG, `px` pixels per grid unit:
+/// free regions white, the rest dark, and `waypoints` (if any) drawn in red.
+/// The image is flipped so the y axis points up, as in [`render_svg`](crate::svg::render_svg).
+pub fn write_png(path: &str, planner: &Planner, waypoints: Option<&[(f64, f64)]>, px: u32) -> io::Result<()> {
+    let px = px.max(1) as usize;
+    let side = planner.size() as usize * px;
+    let mut pixels = vec![BLOCKED; side * side];
+    for r in planner.regions() {
+        let (x0, y0) = (r.x as usize * px, r.y as usize * px);
+        for y in y0..y0 + r.h as usize * px {
+            let row = (side - 1 - y) * side;
+            pixels[row + x0..row + x0 + r.w as usize * px].fill(FREE);
+        }
+    }
+
+    // Sample every half pixel along each leg
+    for leg in waypoints.unwrap_or(&[]).windows(2) {
+        let ((x1, y1), (x2, y2)) = (leg[0], leg[1]);
+        let steps = ((x2 - x1).abs().max((y2 - y1).abs()) * 2.0 * px as f64).ceil().max(1.0) as usize;
+        for s in 0..=steps {
+            let t = s as f64 / steps as f64;
+            let x = ((x1 + t * (x2 - x1)) * px as f64).floor() as i64;
+            let y = ((y1 + t * (y2 - y1)) * px as f64).floor() as i64;
+            if (0..side as i64).contains(&x) && (0..side as i64).contains(&y) {
+                pixels[(side - 1 - y as usize) * side + x as usize] = PATH;
+            }
+        }
+    }
+
+    let mut encoder = Encoder::new(BufWriter::new(File::create(path)?), side as u32, side as u32);
+    encoder.set_color(ColorType::Rgb);
+    encoder.set_depth(BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels.concat())?;
+    writer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::fmt::Write;
+
+use crate::planner::Planner;
+
+/// Escapes the characters that cannot appear verbatim in an SVG attribute.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders the planner's map as a standalone SVG document, `px` pixels per grid
+/// unit: obstacles filled dark, free regions outlined, and `waypoints` (if any)
+/// drawn as a red polyline. The y axis points up, as in the input format.
+/// `labels[i]`, when present, tags obstacle `i` with `class` and `data-label`
+/// attributes for styling; pass `&[]` for unlabeled maps.
+pub fn render_svg(
+    planner: &Planner,
+    labels: &[Option<String>],
+    waypoints: Option<&[(f64, f64)]>,
+    px: f64,
+) -> String {
+    let n = planner.size();
+    let side = n as f64 * px;
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{side}" height="{side}" viewBox="0 0 {n} {n}">"#
+    );
+    let _ = writeln!(out, r#"<g transform="translate(0 {n}) scale(1 -1)">"#);
+    let _ = writeln!(out, r#"<rect x="0" y="0" width="{n}" height="{n}" fill="white"/>"#);
+
+    for r in planner.regions() {
+        let _ = writeln!(
+            out,
+            r##"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="#9ab" stroke-width="0.05"/>"##,
+            r.x, r.y, r.w, r.h
+        );
+    }
+    for (i, r) in planner.obstacles().iter().enumerate() {
+        let tag = match labels.get(i) {
+            Some(Some(label)) => format!(r#" class="{0}" data-label="{0}""#, escape(label)),
+            _ => String::new(),
+        };
+        let _ = writeln!(
+            out,
+            r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#333"{}/>"##,
+            r.x, r.y, r.w, r.h, tag
+        );
+    }
+    if let Some(points) = waypoints {
+        let coords: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+        let _ = writeln!(
+            out,
+            r#"<polyline points="{}" fill="none" stroke="red" stroke-width="0.2"/>"#,
+            coords.join(" ")
+        );
+    }
+
+    out.push_str("</g>\n</svg>\n");
+    out
+}
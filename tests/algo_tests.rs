@@ -1,4 +1,10 @@
-use veloquad::algo::dijkstra;
+use veloquad::algo::{
+    all_pairs_among, coverage_tour, dijkstra, disjoint_paths, dijkstra_distances, dijkstra_epsilon, dijkstra_path,
+    dijkstra_multi_source, dijkstra_single_source, greedy_best_first, reachable_area,
+    resource_constrained_path, time_expanded_path, widest_path, within_hops,
+};
+use veloquad::models::rect::Rect;
+use std::collections::HashMap;
 
 #[test]
 fn test_direct_connection() {
@@ -51,3 +57,242 @@ fn test_longer_chain() {
     let result = dijkstra(&graph, 0, 3);
     assert!((result.unwrap() - 4.5).abs() < 1e-9);
 }
+
+#[test]
+fn test_within_hops_chain() {
+    // 0 - 1 - 2 - 3, weights are ignored by the hop count
+    let mut graph = vec![vec![]; 4];
+    for i in 0..3 {
+        graph[i].push((i + 1, 10.0));
+        graph[i + 1].push((i, 10.0));
+    }
+    assert_eq!(within_hops(&graph, 0, 0), vec![0]);
+    assert_eq!(within_hops(&graph, 0, 2), vec![0, 1, 2]);
+    assert_eq!(within_hops(&graph, 1, 1), vec![1, 0, 2]);
+}
+
+#[test]
+fn test_greedy_follows_heuristic_not_cost() {
+    // 0 at the origin, goal 3 at (10, 0). Node 1 sits right next to the goal but
+    // is reached by an expensive edge; node 2 detours cheaply.
+    let mut centers = HashMap::new();
+    centers.insert(0, (0.0, 0.0));
+    centers.insert(1, (9.0, 0.0));
+    centers.insert(2, (5.0, 5.0));
+    centers.insert(3, (10.0, 0.0));
+
+    let mut graph = vec![vec![]; 4];
+    for &(a, b, w) in &[(0, 1, 20.0), (1, 3, 1.0), (0, 2, 1.0), (2, 3, 1.0)] {
+        graph[a].push((b, w));
+        graph[b].push((a, w));
+    }
+
+    let greedy = greedy_best_first(&graph, &centers, 0, 3).unwrap();
+    assert_eq!(greedy.path, vec![0, 1, 3]);
+    assert!((greedy.cost - 21.0).abs() < 1e-9);
+    assert!((dijkstra(&graph, 0, 3).unwrap() - 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_greedy_no_path() {
+    let mut centers = HashMap::new();
+    centers.insert(0, (0.0, 0.0));
+    centers.insert(1, (1.0, 0.0));
+    let graph = vec![vec![], vec![]];
+    assert!(greedy_best_first(&graph, &centers, 0, 1).is_none());
+}
+
+#[test]
+fn test_dijkstra_distances() {
+    let mut graph = vec![vec![]; 4];
+    for &(a, b, w) in &[(0, 1, 1.0), (1, 2, 2.0), (0, 2, 5.0)] {
+        graph[a].push((b, w));
+        graph[b].push((a, w));
+    }
+    assert_eq!(dijkstra_distances(&graph, 0), vec![0.0, 1.0, 3.0, f64::INFINITY]);
+}
+
+#[test]
+fn test_coverage_tour_visits_reachable_nodes() {
+    // Star: 0 is the hub, leaves at increasing distance; 4 is isolated
+    let mut graph = vec![vec![]; 5];
+    for &(leaf, w) in &[(1, 1.0), (2, 2.0), (3, 3.0)] {
+        graph[0].push((leaf, w));
+        graph[leaf].push((0, w));
+    }
+    let (order, cost) = coverage_tour(&graph, 0);
+    assert_eq!(order, vec![0, 1, 2, 3]);
+    // 0 -> 1 (1), 1 -> 2 via 0 (3), 2 -> 3 via 0 (5)
+    assert!((cost - 9.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_single_source_answers_many_goals() {
+    let mut graph = vec![vec![]; 5];
+    for &(a, b, w) in &[(0, 1, 1.0), (1, 2, 1.0), (0, 2, 5.0), (2, 3, 2.0)] {
+        graph[a].push((b, w));
+        graph[b].push((a, w));
+    }
+    let tree = dijkstra_single_source(&graph, 0);
+    for goal in 0..4 {
+        let direct = dijkstra_path(&graph, 0, goal).unwrap();
+        assert_eq!(tree.path_to(goal).unwrap(), direct.path);
+        assert!((tree.distance(goal).unwrap() - direct.cost).abs() < 1e-9);
+    }
+    assert_eq!(tree.distance(4), None);
+    assert_eq!(tree.path_to(4), None);
+}
+
+fn undirected(n: usize, edges: &[(usize, usize, f64)]) -> Vec<Vec<(usize, f64)>> {
+    let mut graph = vec![vec![]; n];
+    for &(a, b, w) in edges {
+        graph[a].push((b, w));
+        graph[b].push((a, w));
+    }
+    graph
+}
+
+#[test]
+fn test_disjoint_paths_reroutes_around_shortest_path() {
+    // The shortest path 0-1-2-3 would block any second path; the pair avoids edge 1-2
+    let graph = undirected(4, &[(0, 1, 1.0), (1, 2, 1.0), (2, 3, 1.0), (0, 2, 2.0), (1, 3, 2.0)]);
+    let paths = disjoint_paths(&graph, 0, 3);
+
+    assert_eq!(paths.len(), 2);
+    let mut found: Vec<Vec<usize>> = paths.iter().map(|(p, _)| p.clone()).collect();
+    found.sort();
+    assert_eq!(found, vec![vec![0, 1, 3], vec![0, 2, 3]]);
+    assert!((paths[0].1 + paths[1].1 - 6.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_disjoint_paths_single_and_none() {
+    let chain = undirected(4, &[(0, 1, 1.0), (1, 2, 1.0), (2, 3, 1.0)]);
+    let paths = disjoint_paths(&chain, 0, 3);
+    assert_eq!(paths, vec![(vec![0, 1, 2, 3], 3.0)]);
+
+    let split = undirected(4, &[(0, 1, 1.0), (2, 3, 1.0)]);
+    assert!(disjoint_paths(&split, 0, 3).is_empty());
+}
+
+#[test]
+fn test_disjoint_paths_with_zero_weight_cycles() {
+    let graph = undirected(5, &[(0, 1, 0.0), (1, 2, 0.0), (2, 3, 0.0), (0, 4, 0.0), (4, 3, 0.0), (1, 4, 0.0)]);
+    let paths = disjoint_paths(&graph, 0, 3);
+
+    assert_eq!(paths.len(), 2);
+    let mut inner = Vec::new();
+    for (path, cost) in &paths {
+        assert_eq!((path[0], *path.last().unwrap(), *cost), (0, 3, 0.0));
+        assert!(path.windows(2).all(|p| graph[p[0]].iter().any(|&(to, _)| to == p[1])));
+        inner.extend_from_slice(&path[1..path.len() - 1]);
+    }
+    inner.sort();
+    inner.dedup();
+    assert_eq!(inner.len(), paths.iter().map(|(p, _)| p.len() - 2).sum::<usize>());
+}
+
+#[test]
+fn test_all_pairs_among_is_symmetric_distance_matrix() {
+    let graph = undirected(3, &[(0, 1, 1.0), (1, 2, 2.0)]);
+    let centers = HashMap::from([(0, (0.5, 0.5)), (1, (1.5, 0.5)), (2, (3.5, 0.5))]);
+    let matrix = all_pairs_among(&graph, &centers, &[(0, 0), (3, 0), (2, 0)]);
+
+    assert_eq!(
+        matrix,
+        vec![vec![0.0, 3.0, 1.0], vec![3.0, 0.0, 2.0], vec![1.0, 2.0, 0.0]]
+    );
+}
+
+#[test]
+fn test_dijkstra_multi_source_nearest_exit() {
+    // 0 - 1 - 2 - 3 - 4 with exits at both ends
+    let graph = undirected(5, &[(0, 1, 1.0), (1, 2, 1.0), (2, 3, 1.0), (3, 4, 5.0)]);
+    assert_eq!(dijkstra_multi_source(&graph, &[0, 4]), vec![0.0, 1.0, 2.0, 3.0, 0.0]);
+    assert_eq!(dijkstra_multi_source(&graph, &[2]), dijkstra_distances(&graph, 2));
+    assert_eq!(dijkstra_multi_source(&graph, &[]), vec![f64::INFINITY; 5]);
+}
+
+#[test]
+fn test_widest_path_maximizes_bottleneck() {
+    // 0-1-3 is direct but narrow; 0-2-4-3 is longer with a wider bottleneck
+    let graph = undirected(5, &[(0, 1, 1.0), (1, 3, 8.0), (0, 2, 4.0), (2, 4, 3.0), (4, 3, 5.0)]);
+    assert_eq!(widest_path(&graph, 0, 3), Some((vec![0, 2, 4, 3], 3.0)));
+    assert_eq!(widest_path(&graph, 2, 2), Some((vec![2], f64::INFINITY)));
+    assert_eq!(widest_path(&undirected(2, &[]), 0, 1), None);
+}
+
+#[test]
+fn test_reachable_area_within_budget() {
+    let regions = [
+        Rect { x: 0, y: 0, w: 2, h: 2 },
+        Rect { x: 2, y: 0, w: 2, h: 2 },
+        Rect { x: 4, y: 0, w: 4, h: 4 },
+        Rect { x: 0, y: 4, w: 1, h: 1 },
+    ];
+    let graph = undirected(4, &[(0, 1, 2.0), (1, 2, 3.0)]);
+    assert_eq!(reachable_area(&graph, &regions, 0, 0.0), 4);
+    assert_eq!(reachable_area(&graph, &regions, 0, 4.9), 8);
+    assert_eq!(reachable_area(&graph, &regions, 0, 5.0), 24);
+    assert_eq!(reachable_area(&graph, &regions, 0, f64::INFINITY), 24);
+}
+
+#[test]
+fn test_resource_constrained_path_keeps_dominated_costs() {
+    // 0-1-3 is cheap but uses 5 units; 0-2-3 costs more and uses 1
+    let graph = undirected(4, &[(0, 1, 1.0), (1, 3, 1.0), (0, 2, 2.0), (2, 3, 2.0)]);
+    let usage: Vec<Vec<f64>> = graph
+        .iter()
+        .enumerate()
+        .map(|(a, edges)| edges.iter().map(|&(b, _)| if a == 1 || b == 1 { 2.5 } else { 0.5 }).collect())
+        .collect();
+
+    assert_eq!(resource_constrained_path(&graph, &usage, 0, 3, 5.0).unwrap().path, vec![0, 1, 3]);
+    let capped = resource_constrained_path(&graph, &usage, 0, 3, 4.0).unwrap();
+    assert_eq!((capped.path, capped.cost), (vec![0, 2, 3], 4.0));
+    assert!(resource_constrained_path(&graph, &usage, 0, 3, 0.5).is_none());
+}
+
+#[test]
+fn test_time_expanded_path_waits_for_closed_region() {
+    // 0 - 1 - 2, with a detour 0 - 3 - 4 - 2 that is open all along
+    let mut graph = vec![vec![]; 5];
+    for (a, b) in [(0, 1), (1, 2), (0, 3), (3, 4), (4, 2)] {
+        graph[a].push((b, 1.0));
+        graph[b].push((a, 1.0));
+    }
+    let open = time_expanded_path(&graph, &[], 0, 2, 10).unwrap();
+    assert_eq!(open.path, vec![0, 1, 2]);
+
+    // Region 1 is closed during steps 1 and 2: the detour arrives at step 3, waiting
+    // for region 1 only at step 4
+    let closed = [(1, 1, 3)];
+    let timed = time_expanded_path(&graph, &closed, 0, 2, 10).unwrap();
+    assert_eq!(timed.path, vec![0, 3, 4, 2]);
+    assert!(time_expanded_path(&graph, &closed, 0, 2, 2).is_none());
+
+    // Without the detour the path waits at the start
+    graph.truncate(3);
+    graph[0].retain(|&(n, _)| n < 3);
+    graph[2].retain(|&(n, _)| n < 3);
+    let waited = time_expanded_path(&graph, &closed, 0, 2, 10).unwrap();
+    assert_eq!(waited.path, vec![0, 0, 0, 1, 2]);
+    assert_eq!(waited.cost, 2.0);
+}
+
+#[test]
+fn test_dijkstra_epsilon_ignores_rounding_noise() {
+    // Two routes to 3 whose costs differ only by float noise
+    let mut graph = vec![vec![]; 4];
+    graph[0] = vec![(1, 1.0), (2, 1.0 + 1e-10)];
+    graph[1] = vec![(3, 1.0)];
+    graph[2] = vec![(3, 1.0 - 3e-10)];
+
+    let exact = dijkstra_epsilon(&graph, 0, 3, 0.0).unwrap();
+    assert_eq!(exact.path, vec![0, 2, 3]);
+    assert_eq!(exact.path, dijkstra_path(&graph, 0, 3).unwrap().path);
+
+    let relaxed = dijkstra_epsilon(&graph, 0, 3, 1e-6).unwrap();
+    assert_eq!(relaxed.path, vec![0, 1, 3]);
+    assert_eq!(relaxed.cost, 2.0);
+}
@@ -0,0 +1,85 @@
+use veloquad::cache::{load_graph, map_checksum, save_graph, CacheError};
+use veloquad::models::rect::Rect;
+use veloquad::planner::{graph_checksum, PlanOptions, Planner};
+use veloquad::quadtree::free_leaves;
+
+fn temp_cache(tag: &str) -> String {
+    let path = std::env::temp_dir().join(format!("veloquad_cache_{}_{}.txt", tag, std::process::id()));
+    path.to_string_lossy().into_owned()
+}
+
+fn sample_obstacles() -> Vec<Rect> {
+    vec![
+        Rect { x: 2, y: 2, w: 2, h: 4 },
+        Rect { x: 3, y: 1, w: 3, h: 1 },
+    ]
+}
+
+#[test]
+fn test_cache_round_trip() {
+    let obs = sample_obstacles();
+    let planner = Planner::new(8, &obs, &PlanOptions::default()).unwrap();
+    let path = temp_cache("round_trip");
+    save_graph(&path, &planner.to_cache()).unwrap();
+
+    let loaded = load_graph(&path, graph_checksum(8, &obs, &[], &PlanOptions::default())).unwrap();
+    assert_eq!(loaded, planner.to_cache());
+
+    let cached = Planner::from_cache(8, &obs, &[], loaded, &PlanOptions::default()).unwrap();
+    let fresh = planner.query((0, 0), (7, 7)).unwrap();
+    let reused = cached.query((0, 0), (7, 7)).unwrap();
+    assert_eq!(fresh.path, reused.path);
+    assert_eq!(fresh.cost, reused.cost);
+    assert_eq!(free_leaves(cached.quad()), free_leaves(planner.quad()));
+}
+
+#[test]
+fn test_stale_cache_rejected() {
+    let obs = sample_obstacles();
+    let planner = Planner::new(8, &obs, &PlanOptions::default()).unwrap();
+    let path = temp_cache("stale");
+    save_graph(&path, &planner.to_cache()).unwrap();
+
+    let moved = vec![Rect { x: 0, y: 0, w: 1, h: 1 }];
+    let result = load_graph(&path, map_checksum(8, &moved, &[]));
+    assert!(matches!(result, Err(CacheError::Stale { .. })));
+
+    let cache = planner.to_cache();
+    let result = Planner::from_cache(8, &moved, &[], cache, &PlanOptions::default());
+    assert!(matches!(result, Err(CacheError::Stale { .. })));
+}
+
+#[test]
+fn test_cache_from_other_options_rejected() {
+    let obs = sample_obstacles();
+    let inflated = PlanOptions { inflate: (1, 1), ..PlanOptions::default() };
+    let cache = Planner::new(8, &obs, &inflated).unwrap().to_cache();
+    assert_ne!(cache.checksum, map_checksum(8, &obs, &[]));
+
+    let result = Planner::from_cache(8, &obs, &[], cache.clone(), &PlanOptions::default());
+    assert!(matches!(result, Err(CacheError::Stale { .. })));
+    // Query-time options do not matter
+    let smoothed = PlanOptions { center_bias: Some(0.5), ..inflated };
+    assert!(Planner::from_cache(8, &obs, &[], cache, &smoothed).is_ok());
+}
+
+#[test]
+fn test_huge_counts_are_corrupt() {
+    let obs = sample_obstacles();
+    let planner = Planner::new(8, &obs, &PlanOptions::default()).unwrap();
+    let checksum = graph_checksum(8, &obs, &[], &PlanOptions::default());
+    let path = temp_cache("huge");
+    save_graph(&path, &planner.to_cache()).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+    let mut lines: Vec<String> = saved.lines().map(String::from).collect();
+    let regions: usize = lines[2].parse().unwrap();
+
+    lines[2] = usize::MAX.to_string();
+    std::fs::write(&path, lines.join("\n")).unwrap();
+    assert!(matches!(load_graph(&path, checksum), Err(CacheError::Corrupt(_))));
+
+    lines[2] = regions.to_string();
+    lines[3 + regions] = format!("{} 0 1.0", usize::MAX);
+    std::fs::write(&path, lines.join("\n")).unwrap();
+    assert!(matches!(load_graph(&path, checksum), Err(CacheError::Corrupt(_))));
+}
@@ -0,0 +1,30 @@
+use veloquad::algo::dijkstra_path;
+use veloquad::csr::{dijkstra_csr, CsrGraph};
+
+fn sample_graph() -> Vec<Vec<(usize, f64)>> {
+    let mut graph = vec![vec![]; 4];
+    for &(a, b, w) in &[(0, 1, 1.0), (1, 2, 1.0), (0, 2, 5.0), (2, 3, 2.5)] {
+        graph[a].push((b, w));
+        graph[b].push((a, w));
+    }
+    graph
+}
+
+#[test]
+fn test_csr_layout() {
+    let csr = CsrGraph::from_adjacency(&sample_graph());
+    assert_eq!(csr.len(), 4);
+    assert_eq!(csr.offsets, vec![0, 2, 4, 7, 8]);
+    assert_eq!(csr.neighbors(3), (&[2][..], &[2.5][..]));
+}
+
+#[test]
+fn test_csr_dijkstra_matches_nested() {
+    let graph = sample_graph();
+    let csr = CsrGraph::from_adjacency(&graph);
+    let nested = dijkstra_path(&graph, 0, 3).unwrap();
+    let flat = dijkstra_csr(&csr, 0, 3).unwrap();
+    assert_eq!(nested.path, flat.path);
+    assert!((nested.cost - flat.cost).abs() < 1e-9);
+    assert!(dijkstra_csr(&CsrGraph::from_adjacency(&[vec![], vec![]]), 0, 1).is_none());
+}
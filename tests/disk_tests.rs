@@ -0,0 +1,71 @@
+use veloquad::algo::dijkstra_path;
+use veloquad::disk::{dijkstra_disk, write_disk_graph, DiskGraph};
+use veloquad::graph::build_graph;
+use veloquad::models::rect::Rect;
+use veloquad::quadtree::{build_quad, collect_free};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+
+#[test]
+fn test_disk_graph_matches_in_memory() {
+    let obs = vec![
+        Rect { x: 2, y: 2, w: 2, h: 4 },
+        Rect { x: 3, y: 1, w: 3, h: 1 },
+        Rect { x: 2, y: 4, w: 4, h: 1 },
+    ];
+    let quad = build_quad(&obs, 0, 0, 16);
+    let mut centers = HashMap::new();
+    let mut id = 0;
+    collect_free(&quad, &mut id, &mut centers);
+    let mut graph = vec![vec![]; centers.len()];
+    let mut id = 0;
+    build_graph(&quad, &mut id, &mut graph, &centers);
+
+    let path = std::env::temp_dir().join(format!("veloquad_disk_{}.bin", std::process::id()));
+    let path = path.to_str().unwrap();
+    write_disk_graph(path, &quad, &centers).unwrap();
+
+    let mut disk = DiskGraph::open(path).unwrap();
+    assert_eq!(disk.len(), graph.len());
+    for (node, edges) in graph.iter().enumerate() {
+        let mut expected = edges.clone();
+        let mut found = disk.neighbors(node).unwrap();
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        found.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(found, expected);
+    }
+
+    let goal = graph.len() - 1;
+    let memory = dijkstra_path(&graph, 0, goal).unwrap();
+    let on_disk = dijkstra_disk(&mut disk, 0, goal).unwrap().unwrap();
+    assert!((memory.cost - on_disk.cost).abs() < 1e-9);
+}
+
+#[test]
+fn test_disk_graph_rejects_other_files() {
+    let path = std::env::temp_dir().join(format!("veloquad_not_disk_{}.bin", std::process::id()));
+    std::fs::write(&path, b"hello world, not a graph").unwrap();
+    assert!(DiskGraph::open(path.to_str().unwrap()).is_err());
+}
+
+#[test]
+fn test_disk_graph_rejects_bad_nodes_and_offsets() {
+    let quad = build_quad(&[Rect { x: 1, y: 1, w: 2, h: 2 }], 0, 0, 8);
+    let mut centers = HashMap::new();
+    collect_free(&quad, &mut 0, &mut centers);
+    let path = std::env::temp_dir().join(format!("veloquad_disk_bad_{}.bin", std::process::id()));
+    let path = path.to_str().unwrap();
+    write_disk_graph(path, &quad, &centers).unwrap();
+
+    let mut disk = DiskGraph::open(path).unwrap();
+    let node = disk.len();
+    assert_eq!(disk.neighbors(node).unwrap_err().kind(), ErrorKind::InvalidData);
+
+    // Node 0 now ends past the end of the file, and node 1 starts after it ends
+    let mut bytes = std::fs::read(path).unwrap();
+    bytes[32..40].copy_from_slice(&u64::MAX.to_le_bytes());
+    std::fs::write(path, &bytes).unwrap();
+    let mut disk = DiskGraph::open(path).unwrap();
+    assert_eq!(disk.neighbors(0).unwrap_err().kind(), ErrorKind::InvalidData);
+    assert_eq!(disk.neighbors(1).unwrap_err().kind(), ErrorKind::InvalidData);
+}
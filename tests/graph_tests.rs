@@ -1,6 +1,12 @@
 use veloquad::models::rect::Rect;
-use veloquad::quadtree::{build_quad, collect_free};
-use veloquad::graph::{build_graph, find_nearest};
+use veloquad::geometry::shared_border;
+use veloquad::quadtree::{build_quad, build_quad_iterative, collect_free, free_leaves};
+use veloquad::algo::dijkstra;
+use veloquad::graph::{
+    build_graph, apply_axis_scale, apply_heading_bias, apply_size_penalty, build_graph_iterative, central_region,
+    connect_visible, edge_regions, estimate_graph_size, free_perimeter, find_k_nearest, find_nearest, limit_degree,
+    regions_bordering, set_edge_weight, to_adjacency_matrix, EdgeRegions, MatrixTooLarge, MAX_MATRIX_NODES,
+};
 use std::collections::HashMap;
 
 #[test]
@@ -48,3 +54,278 @@ fn test_empty_grid_single_node_no_edges() {
     assert_eq!(graph.len(), 1);
     assert!(graph[0].is_empty());
 }
+
+#[test]
+fn test_iterative_graph_matches_recursive() {
+    let obs = vec![Rect { x: 0, y: 0, w: 1, h: 1 }];
+    let quad = build_quad_iterative(&obs, 0, 0, 1 << 12);
+
+    let mut centers = HashMap::new();
+    let mut id = 0;
+    collect_free(&quad, &mut id, &mut centers);
+
+    let mut recursive = vec![vec![]; centers.len()];
+    let mut id = 0;
+    let ids = build_graph(&quad, &mut id, &mut recursive, &centers);
+
+    let mut iterative = vec![vec![]; centers.len()];
+    let iterative_ids = build_graph_iterative(&quad, &mut iterative, &centers);
+
+    assert_eq!(ids, iterative_ids);
+    assert_eq!(recursive, iterative);
+}
+
+#[test]
+fn test_graph_connects_only_bordering_cells() {
+    // SW quadrant blocked: NW and SE only touch at a corner
+    let obs = vec![Rect { x: 0, y: 0, w: 2, h: 2 }];
+    let quad = build_quad(&obs, 0, 0, 4);
+
+    let mut centers = HashMap::new();
+    let mut id = 0;
+    collect_free(&quad, &mut id, &mut centers);
+
+    let mut graph = vec![vec![]; centers.len()];
+    let mut id = 0;
+    build_graph(&quad, &mut id, &mut graph, &centers);
+
+    // ids: 0 = NW, 1 = NE, 2 = SE
+    assert_eq!(graph[0], vec![(1, 2.0)]);
+    assert_eq!(graph[1].len(), 2);
+    assert_eq!(graph[2], vec![(1, 2.0)]);
+}
+
+#[test]
+fn test_edge_regions() {
+    let mut centers = HashMap::new();
+    centers.insert(0, (1.0, 1.0));
+    centers.insert(1, (7.0, 4.0));
+    centers.insert(2, (4.0, 7.5));
+    centers.insert(3, (0.5, 4.0));
+
+    let edges = edge_regions(8, &centers);
+    assert_eq!(edges, EdgeRegions { north: 2, south: 0, east: 1, west: 3 });
+}
+
+#[test]
+fn test_directed_edge_override() {
+    let mut graph = vec![vec![(1, 1.0)], vec![(0, 1.0)], vec![]];
+    assert!(set_edge_weight(&mut graph, 0, 1, 4.0));
+    assert!(!set_edge_weight(&mut graph, 0, 2, 1.0));
+    assert!((dijkstra(&graph, 0, 1).unwrap() - 4.0).abs() < 1e-9);
+    assert!((dijkstra(&graph, 1, 0).unwrap() - 1.0).abs() < 1e-9);
+
+    // Infinite weight turns the edge into a one-way passage
+    set_edge_weight(&mut graph, 1, 0, f64::INFINITY);
+    assert!(dijkstra(&graph, 1, 0).is_none());
+    assert!(dijkstra(&graph, 0, 1).is_some());
+}
+
+#[test]
+fn test_connect_visible_respects_radius_and_obstacles() {
+    let centers = HashMap::from([(0, (0.5, 0.5)), (1, (3.5, 0.5)), (2, (0.5, 3.5))]);
+    let wall = Rect { x: 1, y: 0, w: 1, h: 2 };
+    let mut graph = vec![vec![]; 3];
+    connect_visible(3.5, &[wall], &mut graph, &centers);
+
+    // 0-1 is blocked by the wall, 1-2 is out of range
+    assert_eq!(graph[0], vec![(2, 3.0)]);
+    assert_eq!(graph[1], vec![]);
+    assert_eq!(graph[2], vec![(0, 3.0)]);
+}
+
+#[test]
+fn test_heading_bias_penalises_backwards_edges() {
+    let centers = HashMap::from([(0, (0.0, 0.0)), (1, (0.0, 2.0)), (2, (2.0, 0.0))]);
+    let mut graph = vec![vec![(1, 2.0), (2, 2.0)], vec![(0, 2.0)], vec![(0, 2.0)]];
+    apply_heading_bias(&mut graph, &centers, (0.0, 1.0), 1.5);
+
+    assert_eq!(graph[0], vec![(1, 2.0), (2, 3.5)]); // forward, sideways
+    assert_eq!(graph[1], vec![(0, 5.0)]); // backwards
+    assert_eq!(graph[2], vec![(0, 3.5)]); // sideways
+}
+
+#[test]
+fn test_size_penalty_favours_large_cells() {
+    let regions = vec![
+        Rect { x: 0, y: 0, w: 4, h: 4 },
+        Rect { x: 4, y: 0, w: 4, h: 4 },
+        Rect { x: 4, y: 4, w: 1, h: 1 },
+    ];
+    let mut graph = vec![vec![(1, 4.0), (2, 4.0)], vec![(0, 4.0)], vec![(0, 4.0)]];
+    apply_size_penalty(&mut graph, &regions, 2.0);
+
+    assert_eq!(graph[0], vec![(1, 6.0), (2, 4.0 * (1.0 + 2.0 / 2.5))]);
+    assert_eq!(graph[1], vec![(0, 6.0)]);
+}
+
+fn region_graph(obs: &[Rect], n: i32) -> Vec<Vec<(usize, f64)>> {
+    let quad = build_quad(obs, 0, 0, n);
+    let mut centers = HashMap::new();
+    let mut id = 0;
+    collect_free(&quad, &mut id, &mut centers);
+    let mut graph = vec![vec![]; centers.len()];
+    let mut id = 0;
+    assert_eq!(build_graph(&quad, &mut id, &mut graph, &centers), (0..centers.len()).collect::<Vec<_>>());
+    graph
+}
+
+#[test]
+fn test_single_region_graph() {
+    let graph = region_graph(&[], 8);
+    assert_eq!(graph, vec![vec![]]);
+    assert_eq!(dijkstra(&graph, 0, 0), Some(0.0));
+}
+
+#[test]
+fn test_two_region_graphs() {
+    // South half blocked: the two northern quadrants share a border
+    let graph = region_graph(&[Rect { x: 0, y: 0, w: 8, h: 4 }], 8);
+    assert_eq!(graph, vec![vec![(1, 4.0)], vec![(0, 4.0)]]);
+    assert_eq!(dijkstra(&graph, 0, 1), Some(4.0));
+
+    // NE and SW blocked: NW and SE only touch at a corner, so no edge
+    let graph = region_graph(&[Rect { x: 4, y: 4, w: 4, h: 4 }, Rect { x: 0, y: 0, w: 4, h: 4 }], 8);
+    assert_eq!(graph, vec![vec![], vec![]]);
+    assert_eq!(dijkstra(&graph, 0, 1), None);
+}
+
+#[test]
+fn test_central_region_area_weighted() {
+    // Blocking the east half shifts the centroid of free space to x = 2
+    let quad = build_quad(&[Rect { x: 4, y: 0, w: 4, h: 8 }], 0, 0, 8);
+    let (id, (cx, cy)) = central_region(&quad).unwrap();
+    assert_eq!((cx, cy), (2.0, 4.0));
+    assert_eq!(id, 0); // NW quadrant, tied with SW and first in id order
+
+    assert_eq!(central_region(&build_quad(&[Rect { x: 0, y: 0, w: 8, h: 8 }], 0, 0, 8)), None);
+}
+
+#[test]
+fn test_central_region_on_huge_leaves() {
+    // Leaves of side 2^17 have areas past i32::MAX
+    let n = 1 << 18;
+    let quad = build_quad(&[Rect { x: 0, y: 0, w: n / 2, h: n / 2 }], 0, 0, n);
+    let (_, (cx, cy)) = central_region(&quad).unwrap();
+    let third = n as f64 / 2.0 + n as f64 / 12.0;
+    assert!((cx - third).abs() < 1e-6 && (cy - third).abs() < 1e-6);
+}
+
+#[test]
+fn test_free_perimeter() {
+    assert_eq!(free_perimeter(&build_quad(&[], 0, 0, 8)), 32.0);
+
+    // A 2x2 hole in the middle adds its own 8 units of border
+    let hole = build_quad(&[Rect { x: 2, y: 2, w: 2, h: 2 }], 0, 0, 8);
+    assert_eq!(free_perimeter(&hole), 40.0);
+
+    // A wall across the whole grid splits it into two 8x3 strips
+    let wall = build_quad(&[Rect { x: 0, y: 3, w: 8, h: 2 }], 0, 0, 8);
+    assert_eq!(free_perimeter(&wall), 44.0);
+}
+
+#[test]
+fn test_find_nearest_breaks_ties_by_lowest_id() {
+    // Four centers equidistant from (2, 2), inserted in scrambled order
+    for _ in 0..20 {
+        let mut centers = HashMap::new();
+        for (id, c) in [(7, (3.0, 3.0)), (3, (1.0, 1.0)), (9, (1.0, 3.0)), (5, (3.0, 1.0))] {
+            centers.insert(id, c);
+        }
+        assert_eq!(find_nearest(2, 2, &centers), 3);
+    }
+}
+
+#[test]
+fn test_regions_bordering_cover_obstacle_perimeter() {
+    let obstacles = [Rect { x: 2, y: 2, w: 2, h: 2 }, Rect { x: 0, y: 6, w: 2, h: 2 }];
+    let quad = build_quad(&obstacles, 0, 0, 8);
+    let leaves = free_leaves(&quad);
+    let border = |obs: &Rect| -> f64 {
+        regions_bordering(obs, &quad)
+            .iter()
+            .map(|&id| {
+                let ((x0, y0), (x1, y1)) = shared_border(obs, &leaves[id]).unwrap();
+                (x1 - x0) + (y1 - y0)
+            })
+            .sum()
+    };
+
+    assert_eq!(border(&obstacles[0]), 8.0);
+    // Corner obstacle: only the two inner sides lie in the grid
+    assert_eq!(border(&obstacles[1]), 4.0);
+    // Touching (4, 4) at a corner only is not bordering
+    let ids = regions_bordering(&obstacles[0], &quad);
+    assert!(ids.iter().all(|&id| leaves[id] != Rect { x: 4, y: 4, w: 4, h: 4 }));
+}
+
+#[test]
+fn test_apply_axis_scale_weights_moves_per_axis() {
+    let mut centers = HashMap::new();
+    centers.insert(0, (0.0, 0.0));
+    centers.insert(1, (2.0, 0.0));
+    centers.insert(2, (0.0, 2.0));
+    let mut graph = vec![vec![(1, 2.0), (2, 4.0)], vec![(0, 2.0)], vec![(0, 4.0)]];
+
+    apply_axis_scale(&mut graph, &centers, (1.0, 3.0));
+    assert_eq!(graph[0], vec![(1, 2.0), (2, 12.0)]);
+    assert_eq!(graph[2], vec![(0, 12.0)]);
+}
+
+#[test]
+fn test_limit_degree_caps_and_stays_symmetric() {
+    let obstacles = [Rect { x: 3, y: 3, w: 2, h: 2 }];
+    let quad = build_quad(&obstacles, 0, 0, 16);
+    let mut centers = HashMap::new();
+    collect_free(&quad, &mut 0, &mut centers);
+    let mut graph = vec![vec![]; centers.len()];
+    build_graph(&quad, &mut 0, &mut graph, &centers);
+    connect_visible(12.0, &obstacles, &mut graph, &centers);
+    assert!(graph.iter().any(|edges| edges.len() > 3));
+
+    limit_degree(&mut graph, 3);
+    for (from, edges) in graph.iter().enumerate() {
+        assert!(edges.len() <= 3);
+        for &(to, w) in edges {
+            assert!(graph[to].contains(&(from, w)));
+        }
+    }
+}
+
+#[test]
+fn test_find_k_nearest_orders_by_distance_then_id() {
+    let mut centers = HashMap::new();
+    for (id, c) in [(4, (9.0, 9.0)), (2, (1.0, 0.0)), (8, (0.0, 1.0)), (1, (5.0, 5.0))] {
+        centers.insert(id, c);
+    }
+    assert_eq!(find_k_nearest(0, 0, &centers, 3), vec![2, 8, 1]);
+    assert_eq!(find_k_nearest(0, 0, &centers, 10), vec![2, 8, 1, 4]);
+    assert_eq!(find_k_nearest(0, 0, &centers, 1)[0], find_nearest(0, 0, &centers));
+}
+
+#[test]
+fn test_adjacency_matrix_and_size_guard() {
+    let graph = vec![vec![(1, 2.0), (1, 1.5)], vec![(0, 1.5)], vec![]];
+    let m = to_adjacency_matrix(&graph).unwrap();
+    assert_eq!(m[0], vec![0.0, 1.5, f64::INFINITY]);
+    assert_eq!(m[1], vec![1.5, 0.0, f64::INFINITY]);
+    assert_eq!(m[2][2], 0.0);
+
+    let huge = vec![vec![]; MAX_MATRIX_NODES + 1];
+    assert_eq!(to_adjacency_matrix(&huge), Err(MatrixTooLarge { nodes: MAX_MATRIX_NODES + 1 }));
+}
+
+#[test]
+fn test_estimate_graph_size_bounds_real_graph() {
+    let obstacles = [Rect { x: 3, y: 3, w: 2, h: 5 }, Rect { x: 9, y: 1, w: 4, h: 2 }];
+    let quad = build_quad(&obstacles, 0, 0, 16);
+    let mut centers = HashMap::new();
+    collect_free(&quad, &mut 0, &mut centers);
+    let mut graph = vec![vec![]; centers.len()];
+    build_graph(&quad, &mut 0, &mut graph, &centers);
+
+    let estimate = estimate_graph_size(&quad);
+    assert_eq!(estimate.regions, free_leaves(&quad).len());
+    assert!(graph.iter().map(Vec::len).sum::<usize>() / 2 <= estimate.edges);
+    assert!(estimate.bytes > 0);
+}
@@ -0,0 +1,29 @@
+use veloquad::geometry::{intersects, shared_border};
+use veloquad::hierarchy::HierarchicalPlanner;
+use veloquad::models::rect::Rect;
+use veloquad::planner::{NoPathReason, PlanError};
+
+#[test]
+fn test_hierarchical_widens_blocked_corridor() {
+    // One-cell wall with a gap at the top: the coarse cells along it look passable
+    // everywhere, so the straight coarse route has to be widened to reach the gap
+    let obstacles = [Rect { x: 12, y: 0, w: 1, h: 28 }];
+    let planner = HierarchicalPlanner::new(32, &obstacles, 8).unwrap();
+    let result = planner.query((2, 2), (28, 2)).unwrap();
+
+    assert!(result.refined > 0);
+    assert!(result.regions.len() > result.coarse_path.len());
+    assert!(result.regions.iter().all(|r| !intersects(&obstacles[0], r.x, r.y, r.w)));
+    assert!(result.regions.windows(2).all(|p| shared_border(&p[0], &p[1]).is_some()));
+    assert!(result.regions.iter().any(|r| r.y >= 28 && r.x <= 12 && r.x + r.w > 12));
+}
+
+#[test]
+fn test_hierarchical_reports_blocked_and_disconnected() {
+    let obstacles = [Rect { x: 12, y: 0, w: 1, h: 32 }, Rect { x: 0, y: 0, w: 2, h: 2 }];
+    let planner = HierarchicalPlanner::new(32, &obstacles, 8).unwrap();
+
+    assert!(matches!(planner.query((1, 1), (4, 4)), Err(PlanError::NoPath(NoPathReason::StartBlocked))));
+    assert!(matches!(planner.query((4, 4), (28, 4)), Err(PlanError::NoPath(NoPathReason::Disconnected))));
+    assert!(planner.query((4, 4), (10, 30)).is_ok());
+}
@@ -0,0 +1,52 @@
+use proptest::prelude::*;
+use veloquad::input::read_input;
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_map(tag: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("veloquad_fuzz_{}_{}.txt", tag, std::process::id()))
+}
+
+/// Lines that look like map content: numbers, floats, signs and stray tokens.
+fn map_like_line() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        prop_oneof![
+            any::<i32>().prop_map(|v| v.to_string()),
+            (0i32..64).prop_map(|v| v.to_string()),
+            any::<f64>().prop_map(|v| v.to_string()),
+            "[a-z+\\-.]{0,3}",
+        ],
+        0..7,
+    )
+    .prop_map(|tokens| tokens.join(" "))
+}
+
+proptest! {
+    #[test]
+    fn fuzz_arbitrary_bytes_never_panic(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+        let path = temp_map("bytes");
+        fs::write(&path, &bytes).unwrap();
+        let _ = read_input(path.to_str().unwrap());
+    }
+
+    #[test]
+    fn fuzz_map_like_lines_never_panic(lines in prop::collection::vec(map_like_line(), 0..12)) {
+        let path = temp_map("lines");
+        fs::write(&path, lines.join("\n")).unwrap();
+        if let Ok(map) = read_input(path.to_str().unwrap()) {
+            prop_assert!(map.n > 0);
+        }
+    }
+
+    #[test]
+    fn fuzz_truncated_obstacle_list_is_err(count in 1usize..20, present in 0usize..20) {
+        prop_assume!(present < count);
+        let mut content = format!("16\n{}\n", count);
+        for _ in 0..present {
+            content.push_str("1 1 2 2\n");
+        }
+        let path = temp_map("truncated");
+        fs::write(&path, content).unwrap();
+        prop_assert!(read_input(path.to_str().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use veloquad::input::{read_input, read_input_str, read_input_str_with, InputError, ObstacleStream, RectFormat};
+use veloquad::models::rect::Rect;
+use std::fs;
+
+fn write_map(name: &str, content: &str) -> String {
+    let path = std::env::temp_dir().join(format!("veloquad_input_{}_{}.txt", name, std::process::id()));
+    fs::write(&path, content).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_read_sample_map() {
+    let map = read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/data/map.txt")).unwrap();
+    assert_eq!(map.n, 8);
+    assert_eq!(map.obstacles.len(), 3);
+    assert_eq!(map.obstacles[0], Rect { x: 2, y: 2, w: 2, h: 4 });
+    assert!(map.terrain.is_empty());
+}
+
+#[test]
+fn test_optional_cost_field() {
+    let path = write_map("cost", "8\n3\n0 0 1 1\n1 1 2 2 0\n4 4 2 2 3.5\n");
+    let map = read_input(&path).unwrap();
+    assert_eq!(map.obstacles.len(), 2);
+    assert_eq!(map.terrain.len(), 1);
+    assert_eq!(map.terrain[0].rect, Rect { x: 4, y: 4, w: 2, h: 2 });
+    assert!((map.terrain[0].cost - 3.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_negative_cost_rejected() {
+    let path = write_map("negative", "8\n1\n0 0 1 1 -2\n");
+    assert!(matches!(read_input(&path), Err(InputError::Parse { line: 3, .. })));
+}
+
+#[test]
+fn test_read_input_str_without_files() {
+    let map = read_input_str("4\n1\n0 0 2 2\n").unwrap();
+    assert_eq!(map.n, 4);
+    assert_eq!(map.obstacles, vec![Rect { x: 0, y: 0, w: 2, h: 2 }]);
+
+    assert!(matches!(read_input_str(""), Err(InputError::Parse { line: 1, .. })));
+    assert!(matches!(read_input_str("0\n0\n"), Err(InputError::Parse { line: 1, .. })));
+    assert!(matches!(read_input_str("4\nx\n"), Err(InputError::Parse { line: 2, .. })));
+    assert!(matches!(read_input_str("4\n1\n0 0 2\n"), Err(InputError::Parse { line: 3, .. })));
+}
+
+#[test]
+fn test_optional_label_field() {
+    let map = read_input_str("8\n4\n0 0 1 1 wall\n1 1 2 2 0 furniture\n4 4 2 2 3.5 mud\n6 6 1 1\n").unwrap();
+    assert_eq!(map.obstacles.len(), 3);
+    assert_eq!(
+        map.obstacle_labels,
+        vec![Some("wall".to_string()), Some("furniture".to_string()), None]
+    );
+    assert_eq!(map.terrain_labels, vec![Some("mud".to_string())]);
+
+    let err = read_input_str("8\n1\n0 0 1 1 2 mud extra\n").unwrap_err();
+    assert!(matches!(err, InputError::Parse { line: 3, .. }));
+}
+
+#[test]
+fn test_fractional_obstacles_snap_outward() {
+    let map = read_input_str("8\n2\n1.5 1.5 2 2.25\n4 4 1 1 2.5\n").unwrap();
+    assert_eq!(map.obstacles, vec![Rect { x: 1, y: 1, w: 3, h: 3 }]);
+    assert_eq!(map.terrain[0].rect, Rect { x: 4, y: 4, w: 1, h: 1 });
+
+    assert!(read_input_str("8\n1\n1.5 1.5 -2 2\n").is_err());
+}
+
+#[test]
+fn test_corner_pairs_in_any_order() {
+    let map = read_input_str_with("8\n3\n1 2 4 6\n4 6 1 2\n5.5 1 3 0.5 2\n", RectFormat::Corners).unwrap();
+    assert_eq!(map.obstacles, vec![Rect { x: 1, y: 2, w: 3, h: 4 }, Rect { x: 1, y: 2, w: 3, h: 4 }]);
+    assert_eq!(map.terrain[0].rect, Rect { x: 3, y: 0, w: 3, h: 1 });
+
+    let huge = format!("8\n1\n{} 0 {} 1\n", i32::MIN, i32::MAX);
+    assert!(matches!(read_input_str_with(&huge, RectFormat::Corners), Err(InputError::Parse { line: 3, .. })));
+}
+
+#[test]
+fn test_obstacle_stream_matches_read_input() {
+    let content = "16\n4\n0 0 2 2\n4 4 2 2 3 mud\n8.5 1 2 2 wall\r\n1 9 3 1\n";
+    let map = read_input_str(content).unwrap();
+    let stream = ObstacleStream::new(content.as_bytes()).unwrap();
+    assert_eq!(stream.n(), 16);
+    let obstacles: Vec<Rect> = stream.collect::<Result<_, _>>().unwrap();
+    assert_eq!(obstacles, map.obstacles);
+
+    let mut truncated = ObstacleStream::new("16\n3\n0 0 2 2\n1 1 x 2\n".as_bytes()).unwrap();
+    assert!(truncated.next().unwrap().is_ok());
+    assert!(matches!(truncated.next(), Some(Err(InputError::Parse { line: 4, .. }))));
+    assert!(truncated.next().is_none());
+    assert!(matches!(ObstacleStream::new("0\n".as_bytes()), Err(InputError::Parse { line: 1, .. })));
+}
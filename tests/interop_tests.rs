@@ -0,0 +1,26 @@
+#![cfg(feature = "petgraph")]
+
+use petgraph::algo::{connected_components, dijkstra};
+use petgraph::graph::NodeIndex;
+use veloquad::algo::dijkstra_distances;
+use veloquad::interop::to_petgraph;
+use veloquad::models::rect::Rect;
+use veloquad::planner::{PlanOptions, Planner};
+
+#[test]
+fn test_petgraph_matches_region_graph() {
+    let obs = vec![Rect { x: 2, y: 2, w: 2, h: 4 }, Rect { x: 3, y: 1, w: 3, h: 1 }];
+    let planner = Planner::new(8, &obs, &PlanOptions::default()).unwrap();
+    let pg = to_petgraph(planner.graph(), planner.centers());
+
+    assert_eq!(pg.node_count(), planner.regions().len());
+    assert_eq!(pg.edge_count(), planner.graph().iter().map(Vec::len).sum::<usize>());
+    assert_eq!(pg[NodeIndex::new(3)], planner.centers()[&3]);
+    assert_eq!(connected_components(&pg), 1);
+
+    let ours = dijkstra_distances(planner.graph(), 0);
+    let theirs = dijkstra(&pg, NodeIndex::new(0), None, |e| *e.weight());
+    for (id, d) in ours.iter().enumerate() {
+        assert!((theirs[&NodeIndex::new(id)] - d).abs() < 1e-9);
+    }
+}
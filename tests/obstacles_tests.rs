@@ -0,0 +1,157 @@
+use proptest::prelude::*;
+use veloquad::models::rect::Rect;
+use veloquad::obstacles::{
+    inflate_obstacles, merge_obstacles, obstacles_bounds, push_centers, region_clearance, snap_outward, transform_cell,
+    transform_map, wrap_obstacles, Transform,
+};
+use std::collections::HashMap;
+
+fn covered(obstacles: &[Rect], x: i32, y: i32) -> bool {
+    obstacles
+        .iter()
+        .any(|r| x >= r.x && x < r.x + r.w && y >= r.y && y < r.y + r.h)
+}
+
+#[test]
+fn test_merge_unit_grid_into_one_block() {
+    let mut units = Vec::new();
+    for x in 0..4 {
+        for y in 0..3 {
+            units.push(Rect { x, y, w: 1, h: 1 });
+        }
+    }
+    assert_eq!(merge_obstacles(&units), vec![Rect { x: 0, y: 0, w: 4, h: 3 }]);
+}
+
+#[test]
+fn test_merge_drops_empty_rects() {
+    let obs = vec![Rect { x: 0, y: 0, w: 0, h: 5 }, Rect { x: 1, y: 1, w: 2, h: -1 }];
+    assert!(merge_obstacles(&obs).is_empty());
+}
+
+#[test]
+fn test_wrap_obstacles_splits_at_seams() {
+    let corner = vec![Rect { x: 7, y: -1, w: 2, h: 2 }];
+    let mut pieces = wrap_obstacles(&corner, 8);
+    pieces.sort_by_key(|r| (r.x, r.y));
+    assert_eq!(
+        pieces,
+        vec![
+            Rect { x: 0, y: 0, w: 1, h: 1 },
+            Rect { x: 0, y: 7, w: 1, h: 1 },
+            Rect { x: 7, y: 0, w: 1, h: 1 },
+            Rect { x: 7, y: 7, w: 1, h: 1 },
+        ]
+    );
+}
+
+#[test]
+fn test_obstacles_bounds() {
+    assert_eq!(obstacles_bounds(&[]), None);
+    let obs = vec![Rect { x: 2, y: 5, w: 3, h: 1 }, Rect { x: -1, y: 0, w: 2, h: 2 }];
+    assert_eq!(obstacles_bounds(&obs), Some(Rect { x: -1, y: 0, w: 6, h: 6 }));
+}
+
+proptest! {
+    #[test]
+    fn prop_merge_preserves_union(
+        rects in prop::collection::vec((0i32..16, 0i32..16, 1i32..6, 1i32..6), 0..20)
+    ) {
+        let obs: Vec<Rect> = rects.into_iter().map(|(x, y, w, h)| Rect { x, y, w, h }).collect();
+        let merged = merge_obstacles(&obs);
+        for x in -1..23 {
+            for y in -1..23 {
+                prop_assert_eq!(covered(&obs, x, y), covered(&merged, x, y));
+            }
+        }
+        // Output rectangles are disjoint
+        for (i, a) in merged.iter().enumerate() {
+            for b in &merged[i + 1..] {
+                let overlap = a.x < b.x + b.w && b.x < a.x + a.w && a.y < b.y + b.h && b.y < a.y + a.h;
+                prop_assert!(!overlap);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_inflate_obstacles_per_axis_and_clamped() {
+    let obs = vec![Rect { x: 4, y: 4, w: 2, h: 2 }, Rect { x: 0, y: 7, w: 1, h: 1 }];
+    assert_eq!(
+        inflate_obstacles(&obs, 1, 2, 8),
+        vec![Rect { x: 3, y: 2, w: 4, h: 6 }, Rect { x: 0, y: 5, w: 2, h: 3 }]
+    );
+}
+
+#[test]
+fn test_region_clearance() {
+    let obs = vec![Rect { x: 4, y: 4, w: 2, h: 2 }];
+    let regions = vec![
+        Rect { x: 0, y: 0, w: 2, h: 2 },
+        Rect { x: 6, y: 4, w: 2, h: 2 },
+        Rect { x: 4, y: 0, w: 2, h: 2 },
+    ];
+    let clearance = region_clearance(&regions, &obs);
+    assert!((clearance[0] - 8f64.sqrt()).abs() < 1e-9);
+    assert_eq!(clearance[1], 0.0);
+    assert_eq!(clearance[2], 2.0);
+
+    assert_eq!(region_clearance(&regions, &[]), vec![f64::INFINITY; 3]);
+}
+
+proptest! {
+    #[test]
+    fn snap_outward_contains_input(x in -100.0f64..100.0, y in -100.0f64..100.0, w in 0.0f64..50.0, h in 0.0f64..50.0) {
+        let r = snap_outward(x, y, w, h).unwrap();
+        prop_assert!(r.x as f64 <= x && r.y as f64 <= y);
+        prop_assert!((r.x + r.w) as f64 >= x + w && (r.y + r.h) as f64 >= y + h);
+        prop_assert!(r.w as f64 <= w + 2.0 && r.h as f64 <= h + 2.0);
+    }
+}
+
+#[test]
+fn test_snap_outward_rounds_away_from_free_space() {
+    assert_eq!(snap_outward(1.2, 2.7, 0.5, 1.0), Some(Rect { x: 1, y: 2, w: 1, h: 2 }));
+    assert_eq!(snap_outward(-0.5, 3.0, 2.0, 1.0), Some(Rect { x: -1, y: 3, w: 3, h: 1 }));
+    assert_eq!(snap_outward(f64::NAN, 0.0, 1.0, 1.0), None);
+    assert_eq!(snap_outward(0.0, 0.0, 1e12, 1.0), None);
+}
+
+#[test]
+fn test_push_centers_moves_away_from_close_obstacles() {
+    let regions = [Rect { x: 0, y: 0, w: 4, h: 4 }, Rect { x: 8, y: 8, w: 4, h: 4 }];
+    let obstacles = [Rect { x: 4, y: 0, w: 2, h: 4 }];
+    let mut centers: HashMap<usize, (f64, f64)> = HashMap::from([(0, (2.0, 2.0)), (1, (10.0, 10.0))]);
+
+    push_centers(&regions, &obstacles, &mut centers, 0.5);
+    assert_eq!(centers[&0], (1.5, 2.0));
+    assert_eq!(centers[&1], (10.0, 10.0));
+
+    // Never leaves the middle half of the region
+    push_centers(&regions, &obstacles, &mut centers, 5.0);
+    assert_eq!(centers[&0], (1.0, 2.0));
+}
+
+#[test]
+fn test_transform_map_round_trips_and_moves_cells() {
+    let obstacles = vec![Rect { x: 1, y: 2, w: 3, h: 1 }, Rect { x: 5, y: 0, w: 2, h: 4 }];
+    let mut turned = obstacles.clone();
+    for _ in 0..4 {
+        turned = transform_map(8, &turned, Transform::Rotate90).1;
+    }
+    assert_eq!(turned, obstacles);
+    let (_, quarter) = transform_map(8, &obstacles, Transform::Rotate90);
+    assert_eq!(transform_map(8, &quarter, Transform::Rotate270).1, obstacles);
+    let (_, mirrored) = transform_map(8, &obstacles, Transform::MirrorY);
+    assert_eq!(transform_map(8, &mirrored, Transform::MirrorY).1, obstacles);
+
+    for t in [Transform::MirrorX, Transform::MirrorY, Transform::Rotate90, Transform::Rotate180, Transform::Rotate270] {
+        let (_, moved) = transform_map(8, &obstacles, t);
+        for x in 0..8 {
+            for y in 0..8 {
+                let (mx, my) = transform_cell(8, (x, y), t);
+                assert_eq!(covered(&obstacles, x, y), covered(&moved, mx, my));
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use veloquad::geometry::{free_hull, segment_intersects};
+use veloquad::models::rect::Rect;
+use veloquad::path::{
+    border_crossing, border_waypoints, corridor_widths, min_corridor_width, path_deltas, path_turns, region_crossings,
+    remove_loops, shortcut_path, simplify_path, smooth_path, timestamp_path,
+};
+
+#[test]
+fn test_segment_intersects_interior_only() {
+    let obs = Rect { x: 2, y: 2, w: 2, h: 2 };
+    assert!(segment_intersects(&obs, (0.0, 3.0), (6.0, 3.0)));
+    // Running along the border or touching a corner is allowed
+    assert!(!segment_intersects(&obs, (0.0, 2.0), (6.0, 2.0)));
+    assert!(!segment_intersects(&obs, (0.0, 0.0), (2.0, 2.0)));
+    assert!(!segment_intersects(&obs, (0.0, 0.0), (1.0, 6.0)));
+}
+
+#[test]
+fn test_shortcut_removes_visible_detour() {
+    let path = vec![(0.0, 0.0), (5.0, 1.0), (10.0, 0.0)];
+    assert_eq!(shortcut_path(&path, &[]), vec![(0.0, 0.0), (10.0, 0.0)]);
+}
+
+#[test]
+fn test_shortcut_keeps_waypoint_around_obstacle() {
+    let obs = vec![Rect { x: 4, y: -2, w: 2, h: 4 }];
+    let path = vec![(0.0, 0.0), (5.0, 4.0), (10.0, 0.0)];
+    assert_eq!(shortcut_path(&path, &obs), path);
+}
+
+#[test]
+fn test_smooth_path_extremes_and_blend() {
+    let path = vec![(0.0, 0.0), (5.0, 2.0), (10.0, 0.0)];
+    assert_eq!(smooth_path(&path, &[], 0.0), vec![(0.0, 0.0), (10.0, 0.0)]);
+    assert_eq!(smooth_path(&path, &[], 1.0), path);
+
+    let half = smooth_path(&path, &[], 0.5);
+    assert_eq!(half.len(), 3);
+    assert!((half[1].0 - 5.0).abs() < 1e-9);
+    assert!((half[1].1 - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_min_corridor_width() {
+    let regions = vec![
+        Rect { x: 0, y: 0, w: 4, h: 4 },
+        Rect { x: 4, y: 0, w: 2, h: 2 },
+        Rect { x: 4, y: 2, w: 2, h: 2 },
+        Rect { x: 6, y: 0, w: 1, h: 1 },
+    ];
+    assert_eq!(min_corridor_width(&[0, 1, 3], &regions), Some(1.0));
+    assert_eq!(min_corridor_width(&[0, 2], &regions), Some(2.0));
+    assert_eq!(min_corridor_width(&[2, 3], &regions), Some(0.0));
+    assert_eq!(min_corridor_width(&[0], &regions), None);
+
+    assert_eq!(corridor_widths(&[0, 1, 3], &regions), vec![(0, 1, 2.0), (1, 3, 1.0)]);
+    assert!(corridor_widths(&[0], &regions).is_empty());
+}
+
+#[test]
+fn test_border_crossing_clamped_to_shared_segment() {
+    let big = Rect { x: 0, y: 0, w: 8, h: 8 };
+    let small = Rect { x: 8, y: 6, w: 2, h: 2 };
+    // The center line would cross x = 8 at y = 6.4, inside the shared [6, 8] span
+    let (x, y) = border_crossing(&big, &small).unwrap();
+    assert_eq!(x, 8.0);
+    assert!((y - 6.4).abs() < 1e-9);
+
+    // A wide neighbour pulls the crossing below its border span: clamp to y = 7
+    let wide = Rect { x: 8, y: 7, w: 8, h: 1 };
+    assert_eq!(border_crossing(&big, &wide), Some((8.0, 7.0)));
+
+    let corner = Rect { x: 8, y: 8, w: 1, h: 1 };
+    assert!(border_crossing(&big, &corner).is_none());
+}
+
+#[test]
+fn test_border_waypoints() {
+    let regions = vec![
+        Rect { x: 0, y: 0, w: 4, h: 4 },
+        Rect { x: 4, y: 0, w: 2, h: 2 },
+        Rect { x: 4, y: 2, w: 2, h: 2 },
+    ];
+    let waypoints = border_waypoints(&[0, 1, 2], &regions);
+    assert_eq!(waypoints.len(), 4);
+    assert_eq!(waypoints[0], (2.0, 2.0));
+    assert_eq!(waypoints[3], (5.0, 3.0));
+    assert_eq!(waypoints[2], (5.0, 2.0));
+    assert_eq!(border_waypoints(&[1], &regions), vec![(5.0, 1.0)]);
+}
+
+#[test]
+fn test_simplify_drops_near_collinear_points() {
+    let path = vec![(0.0, 0.0), (1.0, 0.1), (2.0, -0.1), (3.0, 0.0), (3.0, 4.0)];
+    assert_eq!(
+        simplify_path(&path, 0.5, &[]),
+        vec![(0.0, 0.0), (3.0, 0.0), (3.0, 4.0)]
+    );
+}
+
+#[test]
+fn test_simplify_reinserts_point_around_obstacle() {
+    // The middle point is within epsilon, but the chord clips the obstacle's corner
+    let obs = vec![Rect { x: 4, y: -4, w: 2, h: 4 }];
+    let path = vec![(0.0, -0.2), (5.0, 0.4), (10.0, -0.2)];
+    assert_eq!(simplify_path(&path, 1.0, &obs), path);
+    assert_eq!(simplify_path(&path, 1.0, &[]).len(), 2);
+}
+
+#[test]
+fn test_path_turns_counts_direction_changes() {
+    let centers = std::collections::HashMap::from([
+        (0, (0.0, 0.0)),
+        (1, (2.0, 0.0)),
+        (2, (4.0, 0.0)),
+        (3, (4.0, 2.0)),
+        (4, (2.0, 4.0)),
+    ]);
+    // Straight, then a left quarter turn, then another 45° left
+    let (turns, angle) = path_turns(&[0, 1, 2, 3, 4], &centers);
+    assert_eq!(turns, 2);
+    assert!((angle - 3.0 * std::f64::consts::FRAC_PI_4).abs() < 1e-9);
+
+    assert_eq!(path_turns(&[0, 1], &centers), (0, 0.0));
+}
+
+#[test]
+fn test_region_crossings_gates_on_shared_borders() {
+    let regions = vec![
+        Rect { x: 0, y: 0, w: 2, h: 2 },
+        Rect { x: 2, y: 0, w: 2, h: 2 },
+        Rect { x: 2, y: 2, w: 2, h: 2 },
+    ];
+    let crossings = region_crossings(&[0, 1, 2], &regions);
+
+    assert_eq!(crossings.len(), 3);
+    assert_eq!((crossings[0].entry, crossings[0].exit), ((1.0, 1.0), (2.0, 1.0)));
+    assert_eq!((crossings[1].entry, crossings[1].exit), ((2.0, 1.0), (3.0, 2.0)));
+    assert_eq!((crossings[2].entry, crossings[2].exit), ((3.0, 2.0), (3.0, 3.0)));
+    assert_eq!(crossings[2].region, 2);
+}
+
+#[test]
+fn test_timestamp_path_constant_speed() {
+    let waypoints = [(0.0, 0.0), (3.0, 4.0), (3.0, 6.0)];
+    let timed = timestamp_path(&waypoints, 2.0, 10.0).unwrap();
+    assert_eq!(timed, vec![(0.0, 0.0, 10.0), (3.0, 4.0, 12.5), (3.0, 6.0, 13.5)]);
+    assert!(timestamp_path(&[], 1.0, 0.0).unwrap().is_empty());
+    for speed in [0.0, -1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert!(timestamp_path(&waypoints, speed, 0.0).is_none(), "speed {}", speed);
+    }
+}
+
+#[test]
+fn test_free_hull_of_centers() {
+    let points = [(0.0, 0.0), (4.0, 0.0), (2.0, 0.0), (4.0, 4.0), (0.0, 4.0), (2.0, 2.0), (1.0, 3.0)];
+    let centers: std::collections::HashMap<usize, (f64, f64)> = points.iter().copied().enumerate().collect();
+    assert_eq!(free_hull(&centers), vec![(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+
+    let line: std::collections::HashMap<usize, (f64, f64)> = [(0, (0.0, 1.0)), (1, (2.0, 1.0)), (2, (1.0, 1.0))].into();
+    assert_eq!(free_hull(&line), vec![(0.0, 1.0), (2.0, 1.0)]);
+}
+
+#[test]
+fn test_path_deltas_between_centers() {
+    let centers = std::collections::HashMap::from([(0, (1.0, 1.0)), (1, (3.0, 1.0)), (2, (3.0, 4.5))]);
+    assert_eq!(path_deltas(&[0, 1, 2], &centers), vec![(2.0, 0.0), (0.0, 3.5)]);
+    assert_eq!(path_deltas(&[2], &centers), vec![]);
+}
+
+#[test]
+fn test_remove_loops_cuts_at_crossing() {
+    // Goes right, loops back up and left across its first leg, then continues
+    let looped = [(0.0, 0.0), (4.0, 0.0), (4.0, 2.0), (2.0, 2.0), (2.0, -2.0), (6.0, -2.0)];
+    assert_eq!(remove_loops(&looped), vec![(0.0, 0.0), (2.0, 0.0), (2.0, -2.0), (6.0, -2.0)]);
+
+    let clean = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)];
+    assert_eq!(remove_loops(&clean), clean.to_vec());
+}
+
+#[test]
+fn test_smooth_path_segments_stay_collision_free() {
+    let waypoints = [(0.5, 0.5), (0.5, 4.5), (4.5, 4.5), (4.5, 0.5)];
+    let obstacles = [Rect { x: 2, y: 2, w: 2, h: 2 }];
+    for step in 0..=20 {
+        let bias = step as f64 / 20.0;
+        let smoothed = smooth_path(&waypoints, &obstacles, bias);
+        assert_eq!(smoothed.first(), waypoints.first());
+        assert_eq!(smoothed.last(), waypoints.last());
+        for leg in smoothed.windows(2) {
+            assert!(!segment_intersects(&obstacles[0], leg[0], leg[1]), "bias {}: {:?} crosses", bias, leg);
+        }
+    }
+}
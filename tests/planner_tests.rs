@@ -0,0 +1,604 @@
+use veloquad::models::quad::QuadrantOrder;
+use veloquad::models::rect::Rect;
+use veloquad::models::shape::Shape;
+use veloquad::models::terrain::Terrain;
+use veloquad::planner::{
+    label_center, plan_from_file, plan_with_timeout, HeadingBias, NoPathReason, PlanError, PlanOptions, Planner, SearchMode,
+};
+use veloquad::geometry::{segment_intersects, shared_border};
+use veloquad::graph::ClearanceWeighting;
+use veloquad::input::read_input_str;
+use veloquad::obstacles::{transform_cell, transform_map, Transform};
+use veloquad::quadtree::build_quad;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use std::fs;
+
+fn write_map(name: &str, content: &str) -> String {
+    let path = std::env::temp_dir().join(format!("veloquad_{}_{}.txt", name, std::process::id()));
+    fs::write(&path, content).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_plan_from_file_sample_map() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/map.txt");
+    let result = plan_from_file(path, (0, 0), (7, 7), &PlanOptions::default()).unwrap();
+    assert!(result.cost > 0.0);
+    assert_eq!(result.path.len(), result.waypoints.len());
+    assert!(result.stats.regions > 0);
+}
+
+#[test]
+fn test_plan_result_carries_map() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/map.txt");
+    let result = plan_from_file(path, (0, 0), (7, 7), &PlanOptions::default()).unwrap();
+    assert_eq!(result.n, 8);
+    assert_eq!(result.obstacles.len(), 3);
+    assert_eq!(result.obstacles[0], Rect { x: 2, y: 2, w: 2, h: 4 });
+}
+
+#[test]
+fn test_missing_file_is_io_error() {
+    let result = plan_from_file("/nonexistent/map.txt", (0, 0), (1, 1), &PlanOptions::default());
+    assert!(matches!(result, Err(PlanError::Io(_))));
+}
+
+#[test]
+fn test_malformed_file_is_parse_error() {
+    let path = write_map("malformed", "8\n2\n1 1 2 2\n");
+    let result = plan_from_file(&path, (0, 0), (1, 1), &PlanOptions::default());
+    assert!(matches!(result, Err(PlanError::Parse { line: 4, .. })));
+}
+
+#[test]
+fn test_out_of_bounds_query() {
+    let planner = Planner::new(8, &[], &PlanOptions::default()).unwrap();
+    let result = planner.query((0, 0), (8, 3));
+    assert!(matches!(result, Err(PlanError::InvalidBounds { x: 8, y: 3, n: 8 })));
+}
+
+#[test]
+fn test_fully_blocked_grid_has_no_free_space() {
+    let obs = vec![Rect { x: 0, y: 0, w: 8, h: 8 }];
+    let result = Planner::new(8, &obs, &PlanOptions::default());
+    assert!(matches!(result, Err(PlanError::NoFreeSpace)));
+}
+
+#[test]
+fn test_penalty_terrain_raises_cost() {
+    let plain = Planner::new(8, &[], &PlanOptions::default()).unwrap();
+    assert_eq!(plain.graph().len(), 1);
+
+    let mud = vec![Terrain { rect: Rect { x: 0, y: 0, w: 4, h: 8 }, cost: 3.0 }];
+    let muddy = Planner::with_terrain(8, &[], &mud, &PlanOptions::default()).unwrap();
+    assert_eq!(muddy.graph().len(), 4);
+
+    let result = muddy.query((2, 6), (6, 6)).unwrap();
+    // 4 units between the two halves, charged at the mean of 3.0 and 1.0
+    assert!((result.cost - 8.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_quadrant_order_changes_ids_not_cost() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/map.txt");
+    let default = PlanOptions::default();
+    let bottom_up = PlanOptions { order: QuadrantOrder::SwSeNwNe, ..PlanOptions::default() };
+
+    let a = plan_from_file(path, (0, 0), (7, 7), &default).unwrap();
+    let b = plan_from_file(path, (0, 0), (7, 7), &bottom_up).unwrap();
+    assert_ne!(a.path, b.path);
+    assert!((a.cost - b.cost).abs() < 1e-9);
+    assert_eq!(a.waypoints.first(), b.waypoints.first());
+    assert_eq!(a.waypoints.last(), b.waypoints.last());
+}
+
+#[test]
+fn test_region_rect_matches_center() {
+    let obs = vec![Rect { x: 0, y: 0, w: 2, h: 2 }];
+    let planner = Planner::new(4, &obs, &PlanOptions::default()).unwrap();
+    let result = planner.query((0, 3), (3, 0)).unwrap();
+    for id in result.path {
+        let r = planner.region_rect(id);
+        let (cx, cy) = planner.centers()[&id];
+        assert_eq!((r.x as f64 + r.w as f64 / 2.0, r.y as f64 + r.h as f64 / 2.0), (cx, cy));
+    }
+    assert_eq!(planner.region_rect(0), Rect { x: 0, y: 2, w: 2, h: 2 });
+}
+
+#[test]
+fn test_plan_with_timeout_finishes_in_time() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/map.txt");
+    let options = PlanOptions::default();
+    let timed = plan_with_timeout(path, (0, 0), (7, 7), &options, Duration::from_secs(10)).unwrap();
+    let direct = plan_from_file(path, (0, 0), (7, 7), &options).unwrap();
+    assert_eq!(timed.path, direct.path);
+}
+
+#[test]
+fn test_plan_with_timeout_expires() {
+    // A large map with many scattered obstacles takes far longer than 1ms to build
+    let mut content = String::from("4096\n20000\n");
+    for i in 0..20000 {
+        content.push_str(&format!("{} {} 1 1\n", (i * 37) % 4096, (i * 91) % 4096));
+    }
+    let path = write_map("timeout", &content);
+    let result = plan_with_timeout(&path, (0, 0), (4095, 4095), &PlanOptions::default(), Duration::from_millis(1));
+    assert!(matches!(result, Err(PlanError::Timeout)));
+}
+
+#[test]
+fn test_raised_cancel_flag_stops_build_and_query() {
+    let obstacles = [Rect { x: 2, y: 2, w: 3, h: 3 }];
+    let cancel = Arc::new(AtomicBool::new(false));
+    let options = PlanOptions { cancel: Some(Arc::clone(&cancel)), ..PlanOptions::default() };
+    let planner = Planner::new(16, &obstacles, &options).unwrap();
+    assert!(planner.query((0, 0), (15, 15)).is_ok());
+
+    cancel.store(true, Ordering::Relaxed);
+    assert!(matches!(planner.query((0, 0), (15, 15)), Err(PlanError::Timeout)));
+    assert!(matches!(Planner::new(16, &obstacles, &options), Err(PlanError::Timeout)));
+}
+
+#[test]
+fn test_no_path_reasons() {
+    // A full-height wall at x = 4..6 splits the 8x8 grid in two
+    let wall = vec![Rect { x: 4, y: 0, w: 2, h: 8 }];
+    let planner = Planner::new(8, &wall, &PlanOptions::default()).unwrap();
+
+    let err = planner.query((1, 1), (7, 1)).unwrap_err();
+    assert!(matches!(err, PlanError::NoPath(NoPathReason::Disconnected)));
+
+    let err = planner.query((4, 1), (7, 6)).unwrap_err();
+    assert!(matches!(err, PlanError::NoPath(NoPathReason::StartBlocked)));
+
+    let err = planner.query((2, 6), (5, 3)).unwrap_err();
+    assert!(matches!(err, PlanError::NoPath(NoPathReason::GoalBlocked)));
+}
+
+#[test]
+fn test_wrap_crosses_the_seam() {
+    // A full-height wall at x = 2..6 separates x = 0..2 from x = 6..8 unless the grid wraps
+    let wall = vec![Rect { x: 2, y: 0, w: 4, h: 8 }];
+    let flat = Planner::new(8, &wall, &PlanOptions::default()).unwrap();
+    assert!(flat.query((1, 5), (7, 5)).is_err());
+
+    let torus = Planner::new(8, &wall, &PlanOptions { wrap: true, ..PlanOptions::default() }).unwrap();
+    let result = torus.query((1, 5), (7, 5)).unwrap();
+    assert!(result.cost < 4.0);
+}
+
+#[test]
+fn test_wrap_obstacle_spanning_seam() {
+    // The obstacle hangs off the east edge and re-enters at x = 0..2
+    let obs = vec![Rect { x: 6, y: 0, w: 4, h: 8 }];
+    let torus = Planner::new(8, &obs, &PlanOptions { wrap: true, ..PlanOptions::default() }).unwrap();
+    let area: i32 = torus.regions().iter().map(|r| r.w * r.h).sum();
+    assert_eq!(area, 8 * 4);
+}
+
+#[test]
+fn test_snap_distances_reported() {
+    let planner = Planner::new(8, &[], &PlanOptions::default()).unwrap();
+    // Single region centered at (4, 4)
+    let result = planner.query((4, 4), (0, 0)).unwrap();
+    assert_eq!(result.start_snap, 0.0);
+    assert!((result.goal_snap - 32f64.sqrt()).abs() < 1e-9);
+    assert!(result.snap_exceeds(5.0));
+    assert!(!result.snap_exceeds(6.0));
+}
+
+#[test]
+fn test_look_radius_never_lengthens_path() {
+    let obs = vec![Rect { x: 3, y: 2, w: 2, h: 4 }];
+    let plain = Planner::new(16, &obs, &PlanOptions::default()).unwrap();
+    let look = Planner::new(16, &obs, &PlanOptions { look_radius: Some(8.0), ..PlanOptions::default() }).unwrap();
+    assert!(look.graph().iter().map(Vec::len).sum::<usize>() > plain.graph().iter().map(Vec::len).sum::<usize>());
+
+    let a = plain.query((1, 1), (14, 14)).unwrap();
+    let b = look.query((1, 1), (14, 14)).unwrap();
+    assert!(b.cost <= a.cost + 1e-9);
+    assert!(b.path.len() <= a.path.len());
+}
+
+#[test]
+fn test_planner_exposes_quad() {
+    let obs = vec![Rect { x: 2, y: 2, w: 2, h: 4 }];
+    let planner = Planner::new(8, &obs, &PlanOptions::default()).unwrap();
+    assert_eq!(planner.quad(), &build_quad(&obs, 0, 0, 8));
+}
+
+#[test]
+fn test_insert_and_remove_obstacle_rebuild() {
+    let mut planner = Planner::new(8, &[], &PlanOptions::default()).unwrap();
+    let open = planner.query((0, 3), (7, 3)).unwrap().cost;
+
+    planner.insert_obstacle(Rect { x: 3, y: 0, w: 2, h: 7 }).unwrap();
+    let detour = planner.query((0, 3), (7, 3)).unwrap().cost;
+    assert!(detour > open);
+
+    assert_eq!(planner.remove_obstacle(0).unwrap(), Some(Rect { x: 3, y: 0, w: 2, h: 7 }));
+    assert!(planner.obstacles().is_empty());
+    assert_eq!(planner.query((0, 3), (7, 3)).unwrap().cost, open);
+    assert_eq!(planner.remove_obstacle(0).unwrap(), None);
+
+    // Filling the grid fails and leaves the planner usable
+    assert!(planner.insert_obstacle(Rect { x: 0, y: 0, w: 8, h: 8 }).is_err());
+    assert_eq!(planner.obstacles().len(), 0);
+}
+
+#[test]
+fn test_inflate_option_closes_narrow_gap() {
+    // A one-cell vertical gap at x = 4 between two walls
+    let obs = vec![Rect { x: 0, y: 4, w: 4, h: 1 }, Rect { x: 5, y: 4, w: 3, h: 1 }];
+    let start = (4, 0);
+    let goal = (4, 7);
+    assert!(Planner::new(8, &obs, &PlanOptions::default()).unwrap().query(start, goal).is_ok());
+
+    // Growing along x seals the gap, growing along y alone does not
+    let wide = PlanOptions { inflate: (1, 0), ..PlanOptions::default() };
+    assert!(Planner::new(8, &obs, &wide).unwrap().query(start, goal).is_err());
+    let tall = PlanOptions { inflate: (0, 1), ..PlanOptions::default() };
+    assert!(Planner::new(8, &obs, &tall).unwrap().query(start, goal).is_ok());
+}
+
+#[test]
+fn test_query_region_reaches_cheapest_overlapping_region() {
+    let obs = vec![Rect { x: 4, y: 0, w: 1, h: 6 }];
+    let planner = Planner::new(8, &obs, &PlanOptions::default()).unwrap();
+
+    // A pad straddling the wall: the near side is reached without crossing it
+    let pad = Rect { x: 2, y: 2, w: 5, h: 2 };
+    let plan = planner.query_region((0, 0), pad).unwrap();
+    let reached = planner.region_rect(*plan.path.last().unwrap());
+    assert!(reached.x + reached.w <= 4);
+    assert_eq!(plan.goal_snap, 0.0);
+    assert!(plan.cost <= planner.query((0, 0), (6, 3)).unwrap().cost);
+
+    let walled = Rect { x: 4, y: 0, w: 1, h: 6 };
+    assert!(matches!(
+        planner.query_region((0, 0), walled),
+        Err(PlanError::NoPath(NoPathReason::GoalBlocked))
+    ));
+}
+
+#[test]
+fn test_obstacle_edit_inside_blocked_space_keeps_graph() {
+    let mut planner = Planner::new(8, &[Rect { x: 2, y: 2, w: 4, h: 4 }], &PlanOptions::default()).unwrap();
+    let (a, b) = (0, planner.graph()[0][0].0);
+    assert!(planner.set_edge_weight(a, b, 42.0));
+
+    // Fully inside the existing obstacle: same tree, graph and overrides kept
+    planner.insert_obstacle(Rect { x: 3, y: 3, w: 2, h: 2 }).unwrap();
+    assert!(!planner.regions_rebuilt());
+    assert_eq!(planner.obstacles().len(), 2);
+    assert!(planner.graph()[a].contains(&(b, 42.0)));
+
+    // Removing the outer obstacle frees space, so the regions are recomputed
+    planner.remove_obstacle(0).unwrap().unwrap();
+    assert!(planner.regions_rebuilt());
+    assert!(!planner.graph()[a].contains(&(b, 42.0)));
+}
+
+#[test]
+fn test_empty_map_single_region_query() {
+    let planner = Planner::new(8, &[], &PlanOptions::default()).unwrap();
+    assert_eq!(planner.regions().len(), 1);
+
+    let plan = planner.query((0, 0), (7, 7)).unwrap();
+    assert_eq!(plan.path, vec![0]);
+    assert_eq!(plan.cost, 0.0);
+    assert_eq!(plan.waypoints, vec![(4.0, 4.0)]);
+}
+
+#[test]
+fn test_stats_to_json() {
+    let planner = Planner::new(8, &[Rect { x: 2, y: 2, w: 2, h: 4 }], &PlanOptions::default()).unwrap();
+    let plan = planner.query((0, 0), (7, 7)).unwrap();
+    let json = plan.stats.to_json();
+
+    assert!(json.starts_with("{\"build_time\":"));
+    assert!(json.contains(&format!("\"regions\":{},", planner.regions().len())));
+    assert!(json.contains(&format!("\"expansions\":{}}}", plan.stats.expansions)));
+}
+
+#[test]
+fn test_reject_blocked_endpoints() {
+    let wall = Rect { x: 3, y: 0, w: 2, h: 6 };
+    let snapping = Planner::new(8, &[wall], &PlanOptions::default()).unwrap();
+    assert!(snapping.query((3, 2), (7, 7)).is_ok());
+
+    let options = PlanOptions { reject_blocked_endpoints: true, ..PlanOptions::default() };
+    let strict = Planner::new(8, &[wall], &options).unwrap();
+    assert!(matches!(
+        strict.query((3, 2), (7, 7)),
+        Err(PlanError::InsideObstacle { x: 3, y: 2, obstacle }) if obstacle == wall
+    ));
+    assert!(matches!(strict.query((0, 0), (4, 5)), Err(PlanError::InsideObstacle { y: 5, .. })));
+    assert!(strict.query((0, 0), (7, 7)).is_ok());
+}
+
+#[test]
+fn test_replan_reuses_unaffected_path() {
+    let wall = Rect { x: 8, y: 0, w: 2, h: 12 };
+    let mut planner = Planner::new(16, &[wall], &PlanOptions::default()).unwrap();
+    let (start, goal) = ((1, 1), (14, 1));
+    let first = planner.query(start, goal).unwrap();
+
+    // An obstacle far from the path leaves it valid
+    let far = Rect { x: 14, y: 14, w: 2, h: 2 };
+    planner.insert_obstacle(far).unwrap();
+    let reused = planner.replan(&first, far, start, goal).unwrap();
+    assert_eq!(reused.stats.expansions, 0);
+    assert_eq!(reused.waypoints, first.waypoints);
+    assert_eq!(reused.regions, first.regions);
+    assert!((reused.cost - planner.query(start, goal).unwrap().cost).abs() < 1e-9);
+
+    // Closing the gap above the wall forces a fresh search
+    let block = Rect { x: 8, y: 12, w: 2, h: 4 };
+    planner.insert_obstacle(block).unwrap();
+    assert!(planner.replan(&reused, block, start, goal).is_err());
+}
+
+#[test]
+fn test_astar_matches_dijkstra_under_axis_scale() {
+    let obstacles = [
+        Rect { x: 4, y: 0, w: 2, h: 20 },
+        Rect { x: 10, y: 8, w: 14, h: 2 },
+        Rect { x: 20, y: 14, w: 2, h: 18 },
+    ];
+    let scaled = PlanOptions { axis_scale: Some((1.0, 3.0)), ..PlanOptions::default() };
+    let astar = PlanOptions { search: SearchMode::AStar, ..scaled.clone() };
+    let dijkstra = Planner::new(32, &obstacles, &scaled).unwrap();
+    let guided = Planner::new(32, &obstacles, &astar).unwrap();
+
+    for (start, goal) in [((0, 0), (31, 31)), ((8, 2), (30, 2)), ((0, 31), (12, 0))] {
+        let a = dijkstra.query(start, goal).unwrap();
+        let b = guided.query(start, goal).unwrap();
+        assert!((a.cost - b.cost).abs() < 1e-9, "{:?} -> {:?}", start, goal);
+        assert!(b.stats.expansions <= a.stats.expansions);
+    }
+}
+
+#[test]
+fn test_query_excluding_routes_around_held_regions() {
+    let planner = Planner::new(8, &[Rect { x: 4, y: 2, w: 1, h: 4 }], &PlanOptions::default()).unwrap();
+    let direct = planner.query((0, 3), (7, 3)).unwrap();
+    let held: HashSet<usize> = direct.path[1..direct.path.len() - 1].iter().copied().collect();
+
+    let detour = planner.query_excluding((0, 3), (7, 3), &held).unwrap();
+    assert!(detour.path.iter().all(|id| !held.contains(id)));
+    assert!(detour.cost > direct.cost);
+    // Nothing is removed from the graph
+    assert_eq!(planner.query((0, 3), (7, 3)).unwrap().path, direct.path);
+
+    let goal: HashSet<usize> = [*direct.path.last().unwrap()].into();
+    assert!(matches!(
+        planner.query_excluding((0, 3), (7, 3), &goal),
+        Err(PlanError::NoPath(NoPathReason::Disconnected))
+    ));
+    // Even when start and goal share the excluded region
+    assert!(matches!(
+        planner.query_excluding((7, 3), (7, 3), &goal),
+        Err(PlanError::NoPath(NoPathReason::Disconnected))
+    ));
+}
+
+#[test]
+fn test_query_widest_prefers_wide_gap() {
+    // Wall at x = 8 with a one-cell gap at y = 2 and a four-cell gap at y = 8..12
+    let obstacles = [
+        Rect { x: 8, y: 0, w: 1, h: 2 },
+        Rect { x: 8, y: 3, w: 1, h: 5 },
+        Rect { x: 8, y: 12, w: 1, h: 4 },
+    ];
+    let planner = Planner::new(16, &obstacles, &PlanOptions::default()).unwrap();
+    let shortest = planner.query((0, 2), (15, 2)).unwrap();
+    let (widest, width) = planner.query_widest((0, 2), (15, 2)).unwrap();
+
+    assert!(shortest.regions.iter().any(|r| r.x == 8 && r.y == 2));
+    assert!(widest.regions.iter().any(|r| r.x == 8 && r.y == 8));
+    assert!(width > 1.0);
+    assert!(widest.cost > shortest.cost);
+}
+
+#[test]
+fn test_query_terrain_capped_avoids_mud_past_the_cap() {
+    // Wall at x = 8 with a muddy gap at the bottom and a clear one at the top
+    let obstacles = [Rect { x: 8, y: 2, w: 1, h: 12 }];
+    let mud = [Terrain { rect: Rect { x: 6, y: 0, w: 4, h: 2 }, cost: 1.5 }];
+    let planner = Planner::with_terrain(16, &obstacles, &mud, &PlanOptions::default()).unwrap();
+    let in_mud = |r: &Rect| r.x < 10 && r.x + r.w > 6 && r.y < 2;
+
+    let free = planner.query((0, 0), (15, 0)).unwrap();
+    assert!(free.regions.iter().any(in_mud));
+    let loose = planner.query_terrain_capped((0, 0), (15, 0), 100.0).unwrap();
+    assert!((loose.cost - free.cost).abs() < 1e-9);
+
+    let dry = planner.query_terrain_capped((0, 0), (15, 0), 0.0).unwrap();
+    assert!(!dry.regions.iter().any(in_mud));
+    assert!(dry.cost > free.cost);
+}
+
+#[test]
+fn test_label_center_resolves_named_endpoints() {
+    let map = read_input_str("16\n4\n0 12 4 4 2 kitchen\n10 0 6 2 2 door\n6 6 2 2 shelf\n9 9 1 1 shelf\n").unwrap();
+    assert_eq!(label_center(&map, "kitchen").unwrap(), (2, 14));
+    assert_eq!(label_center(&map, "door").unwrap(), (13, 1));
+
+    let planner = Planner::with_terrain(map.n, &map.obstacles, &map.terrain, &PlanOptions::default()).unwrap();
+    let from = label_center(&map, "kitchen").unwrap();
+    let to = label_center(&map, "door").unwrap();
+    assert!(planner.query(from, to).unwrap().cost > 0.0);
+
+    assert!(matches!(label_center(&map, "garage"), Err(PlanError::UnknownLabel(l)) if l == "garage"));
+    assert!(matches!(label_center(&map, "shelf"), Err(PlanError::AmbiguousLabel { count: 2, .. })));
+}
+
+#[test]
+fn test_clearance_weighting_hugs_or_clears() {
+    let obstacles = [Rect { x: 6, y: 4, w: 4, h: 8 }];
+    let mean_clearance = |mode| {
+        let options = PlanOptions { clearance_weighting: Some(mode), ..PlanOptions::default() };
+        let planner = Planner::new(16, &obstacles, &options).unwrap();
+        let plan = planner.query((1, 8), (14, 8)).unwrap();
+        let clearance = planner.clearance();
+        plan.path.iter().map(|&id| clearance[id]).sum::<f64>() / plan.path.len() as f64
+    };
+
+    let hug = mean_clearance(ClearanceWeighting::Hug(4.0));
+    let clear = mean_clearance(ClearanceWeighting::Clear(4.0));
+    assert!(hug < clear, "hug {} clear {}", hug, clear);
+}
+
+#[test]
+fn test_sealed_endpoint_falls_back_to_connected_region() {
+    // The free cell (0, 0) is walled in on every side, corners included
+    let obs = [Rect { x: 1, y: 0, w: 1, h: 2 }, Rect { x: 0, y: 1, w: 1, h: 1 }];
+    let planner = Planner::new(8, &obs, &PlanOptions::default()).unwrap();
+    let result = planner.query((0, 0), (7, 7)).unwrap();
+    assert_ne!(planner.region_rect(result.path[0]), Rect { x: 0, y: 0, w: 1, h: 1 });
+    assert!(result.start_snap > 0.5);
+}
+
+#[test]
+fn test_medial_axis_keeps_to_corridor_middle() {
+    let walls = [Rect { x: 0, y: 0, w: 16, h: 4 }, Rect { x: 0, y: 12, w: 16, h: 4 }];
+    let options = PlanOptions { medial_axis: true, ..PlanOptions::default() };
+    let planner = Planner::new(16, &walls, &options).unwrap();
+    let result = planner.query((1, 5), (14, 10)).unwrap();
+    assert!(result.waypoints.len() > 2);
+    for &(_, y) in &result.waypoints {
+        assert!((y - 8.0).abs() <= 0.5, "waypoint at y = {} is off the corridor middle", y);
+    }
+    let plain = Planner::new(16, &walls, &PlanOptions::default()).unwrap();
+    assert!(plain.query((1, 5), (14, 10)).unwrap().waypoints.iter().any(|&(_, y)| (y - 8.0).abs() > 1.0));
+}
+
+#[test]
+fn test_query_timed_waits_for_moving_obstacle() {
+    let walls = [Rect { x: 0, y: 0, w: 16, h: 6 }, Rect { x: 0, y: 10, w: 16, h: 6 }];
+    let planner = Planner::new(16, &walls, &PlanOptions::default()).unwrap();
+    let direct = planner.query((1, 8), (14, 8)).unwrap();
+    let gate = (Rect { x: 6, y: 6, w: 4, h: 4 }, 0, 20);
+    let result = planner.query_timed((1, 8), (14, 8), &[gate], 40).unwrap();
+    assert!(result.path.len() > direct.path.len());
+    assert!(result.path.windows(2).any(|w| w[0] == w[1]));
+    assert_eq!(result.path.last(), direct.path.last());
+    assert!(planner.query_timed((1, 8), (14, 8), &[gate], 10).is_err());
+}
+
+#[test]
+fn test_plan_cost_is_orientation_independent() {
+    let obstacles = [Rect { x: 2, y: 0, w: 2, h: 12 }, Rect { x: 8, y: 5, w: 6, h: 3 }];
+    // Endpoints well inside large regions, so snapping is unambiguous in every orientation
+    let (start, goal) = ((1, 13), (13, 2));
+    let base = Planner::new(16, &obstacles, &PlanOptions::default()).unwrap().query(start, goal).unwrap();
+    for t in [Transform::MirrorX, Transform::MirrorY, Transform::Rotate90, Transform::Rotate180, Transform::Rotate270] {
+        let (n, moved) = transform_map(16, &obstacles, t);
+        let planner = Planner::new(n, &moved, &PlanOptions::default()).unwrap();
+        let result = planner.query(transform_cell(n, start, t), transform_cell(n, goal, t)).unwrap();
+        assert!((result.cost - base.cost).abs() < 1e-9, "{:?}: {} vs {}", t, result.cost, base.cost);
+    }
+}
+
+#[test]
+fn test_cell_sizes_follow_path() {
+    let obstacles = [Rect { x: 7, y: 0, w: 1, h: 15 }];
+    let planner = Planner::new(16, &obstacles, &PlanOptions::default()).unwrap();
+    let result = planner.query((2, 2), (13, 2)).unwrap();
+    let sizes = result.cell_sizes();
+    assert_eq!(sizes.len(), result.path.len());
+    for (&size, &id) in sizes.iter().zip(&result.path) {
+        assert_eq!(size, planner.region_rect(id).w);
+    }
+    // Squeezing past the end of the wall goes through unit cells
+    assert!(sizes.contains(&1));
+}
+
+#[test]
+fn test_query_within_keeps_path_inside_area() {
+    let wall = [Rect { x: 6, y: 4, w: 4, h: 8 }];
+    let planner = Planner::new(16, &wall, &PlanOptions::default()).unwrap();
+    let south = Rect { x: 0, y: 0, w: 16, h: 8 };
+    let result = planner.query_within((1, 6), (14, 6), &Shape::Rect(south)).unwrap();
+    for r in &result.regions {
+        assert!(r.y + r.h <= 8, "region {:?} leaves the allowed area", r);
+    }
+
+    let band = Shape::Rect(Rect { x: 0, y: 4, w: 16, h: 8 });
+    let cut_off = planner.query_within((1, 6), (14, 6), &band);
+    assert!(matches!(cut_off, Err(PlanError::NoPath(NoPathReason::Disconnected))));
+    let outside = planner.query_within((1, 1), (14, 6), &band);
+    assert!(matches!(outside, Err(PlanError::NoPath(NoPathReason::OutsideArea))));
+}
+
+#[test]
+fn test_remove_obstacle_coarsening_to_no_free_space_is_an_error() {
+    // Two obstacles allow unit cells; with one left, cells stay whole and the only
+    // cell, partly blocked, counts as blocked
+    let obstacles = [Rect { x: 0, y: 0, w: 1, h: 1 }, Rect { x: 3, y: 3, w: 1, h: 1 }];
+    let options = PlanOptions { adaptive_sides: Some(vec![(0, 4), (2, 1)]), ..PlanOptions::default() };
+    let mut planner = Planner::new(4, &obstacles, &options).unwrap();
+    assert!(matches!(planner.remove_obstacle(1), Err(PlanError::NoFreeSpace)));
+    assert_eq!(planner.obstacles(), &obstacles[..]);
+}
+
+#[test]
+fn test_bidirectional_matches_dijkstra_with_heading_bias() {
+    let obstacles = [Rect { x: 5, y: 3, w: 2, h: 9 }, Rect { x: 10, y: 6, w: 4, h: 2 }];
+    let bias = Some(HeadingBias { direction: (1.0, 1.0), weight: 4.0 });
+    for (start, goal) in [((15, 15), (0, 0)), ((0, 0), (15, 15)), ((1, 14), (14, 1))] {
+        let cost = |search| {
+            let options = PlanOptions { heading_bias: bias, search, ..PlanOptions::default() };
+            Planner::new(16, &obstacles, &options).unwrap().query(start, goal).unwrap().cost
+        };
+        let (dijkstra, both) = (cost(SearchMode::Dijkstra), cost(SearchMode::Bidirectional));
+        assert!((dijkstra - both).abs() < 1e-9, "{:?} -> {:?}: {} vs {}", start, goal, dijkstra, both);
+    }
+}
+
+#[test]
+fn test_post_processing_respects_inflation_margin() {
+    let obstacles = [Rect { x: 6, y: 6, w: 4, h: 4 }];
+    let inflated = Rect { x: 4, y: 4, w: 8, h: 8 };
+    for (center_bias, medial_axis) in [(Some(0.0), false), (Some(0.5), false), (None, true)] {
+        let options = PlanOptions { inflate: (2, 2), center_bias, medial_axis, ..PlanOptions::default() };
+        let result = Planner::new(16, &obstacles, &options).unwrap().query((2, 13), (13, 2)).unwrap();
+        for leg in result.waypoints.windows(2) {
+            assert!(!segment_intersects(&inflated, leg[0], leg[1]), "{:?} enters the margin", leg);
+        }
+    }
+}
+
+#[test]
+fn test_regions_link_only_across_shared_borders() {
+    // The two free cells sit in the same quadtree node but only meet at a corner;
+    // linking every pair of free siblings used to give a path of cost √2 here
+    let diagonal = [Rect { x: 0, y: 1, w: 1, h: 1 }, Rect { x: 1, y: 0, w: 1, h: 1 }];
+    let planner = Planner::new(2, &diagonal, &PlanOptions::default()).unwrap();
+    let err = planner.query((0, 0), (1, 1)).unwrap_err();
+    assert!(matches!(err, PlanError::NoPath(NoPathReason::Disconnected)));
+
+    let obstacles = [Rect { x: 4, y: 0, w: 2, h: 12 }, Rect { x: 10, y: 4, w: 2, h: 12 }];
+    let planner = Planner::new(16, &obstacles, &PlanOptions::default()).unwrap();
+    let result = planner.query((1, 1), (14, 14)).unwrap();
+    let regions = planner.regions();
+    for pair in result.path.windows(2) {
+        assert!(shared_border(&regions[pair[0]], &regions[pair[1]]).is_some(), "{:?} do not touch", pair);
+    }
+}
+
+#[test]
+fn test_recursive_build_limit_keeps_the_plan() {
+    let obstacles = [Rect { x: 3, y: 0, w: 2, h: 12 }, Rect { x: 9, y: 5, w: 4, h: 2 }];
+    let plan = |limit| {
+        let options = PlanOptions { recursive_build_limit: limit, ..PlanOptions::default() };
+        Planner::new(16, &obstacles, &options).unwrap().query((0, 0), (15, 15)).unwrap()
+    };
+    let (recursive, iterative) = (plan(None), plan(Some(4)));
+    assert_eq!(recursive.path, iterative.path);
+    assert_eq!(recursive.cost, iterative.cost);
+}
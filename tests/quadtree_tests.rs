@@ -1,6 +1,15 @@
 use veloquad::models::rect::Rect;
 use veloquad::models::quad::Quad;
-use veloquad::quadtree::{build_quad, collect_free};
+use veloquad::models::shape::Shape;
+use veloquad::models::terrain::Terrain;
+use veloquad::quadtree::{
+    build_quad, build_quad_adaptive, build_quad_auto, build_quad_from_free, build_quad_iterative, build_quad_window,
+    build_shape_quad, build_terrain_quad, collect_free, density_min_side, free_leaves, leaf_at_point, leaf_neighbors,
+    merge_free_leaves, morton_code, morton_leaves, quad_diff, raycast, segment_is_free, smallest_enclosing_node_mut,
+    terrain_costs, validate, Change, RECURSIVE_BUILD_LIMIT,
+};
+use veloquad::geometry::{intersects, segment_intersects, shared_border};
+use proptest::prelude::*;
 use std::collections::HashMap;
 
 #[test]
@@ -42,3 +51,305 @@ fn test_collect_free_center_position() {
     assert!((cx - 2.0).abs() < 1e-9);
     assert!((cy - 2.0).abs() < 1e-9);
 }
+
+#[test]
+fn test_iterative_build_matches_recursive() {
+    let obs = vec![
+        Rect { x: 2, y: 2, w: 2, h: 4 },
+        Rect { x: 3, y: 1, w: 3, h: 1 },
+        Rect { x: 2, y: 4, w: 4, h: 1 },
+    ];
+    assert_eq!(build_quad_iterative(&obs, 0, 0, 8), build_quad(&obs, 0, 0, 8));
+}
+
+#[test]
+fn test_iterative_build_deep_tree() {
+    // A unit obstacle in the corner of a 2^30 grid forces the maximum subdivision depth
+    let n = 1 << 30;
+    let obs = vec![Rect { x: 0, y: 0, w: 1, h: 1 }];
+    let quad = build_quad_iterative(&obs, 0, 0, n);
+
+    let mut depth = 0;
+    let mut node = &quad;
+    while let Quad::Split(children) = node {
+        depth += 1;
+        node = &children[2];
+    }
+    assert_eq!(depth, 30);
+    assert!(matches!(node, Quad::Blocked));
+}
+
+#[test]
+fn test_terrain_splits_and_costs_leaves() {
+    let mud = vec![Terrain { rect: Rect { x: 0, y: 0, w: 2, h: 2 }, cost: 4.0 }];
+    let quad = build_terrain_quad(&[], &mud, 0, 0, 4);
+    assert!(matches!(quad, Quad::Split(_)));
+
+    let leaves = free_leaves(&quad);
+    assert_eq!(leaves.len(), 4);
+    // ids follow NW, NE, SW, SE order
+    assert_eq!(terrain_costs(&leaves, &mud), vec![1.0, 1.0, 4.0, 1.0]);
+}
+
+#[test]
+fn test_hollow_obstacle_leaves_interior_free() {
+    let ring = Shape::hollow(Rect { x: 0, y: 0, w: 8, h: 8 }, Rect { x: 2, y: 2, w: 4, h: 4 });
+    let quad = build_shape_quad(&[ring], 0, 0, 8);
+
+    let leaves = free_leaves(&quad);
+    let area: i32 = leaves.iter().map(|r| r.w * r.h).sum();
+    assert_eq!(area, 16);
+    for r in &leaves {
+        assert!(r.x >= 2 && r.y >= 2 && r.x + r.w <= 6 && r.y + r.h <= 6);
+    }
+}
+
+#[test]
+fn test_union_obstacle_matches_separate_rects() {
+    let a = Rect { x: 0, y: 0, w: 3, h: 2 };
+    let b = Rect { x: 5, y: 4, w: 2, h: 3 };
+    let union = Shape::Union(vec![Shape::Rect(a), Shape::Rect(b)]);
+    assert_eq!(build_shape_quad(&[union], 0, 0, 8), build_quad(&[a, b], 0, 0, 8));
+}
+
+#[test]
+fn test_build_from_free_inverts_semantics() {
+    let room = Rect { x: 0, y: 0, w: 2, h: 2 };
+    assert_eq!(build_quad_from_free(&[], 0, 0, 4), Quad::Blocked);
+    assert_eq!(build_quad_from_free(&[Rect { x: 0, y: 0, w: 4, h: 4 }], 0, 0, 4), Quad::Free(0, 0, 4));
+
+    let quad = build_quad_from_free(&[room], 0, 0, 4);
+    assert_eq!(free_leaves(&quad), vec![room]);
+
+    // Two free rooms that only jointly cover a quadrant are still fully free
+    let halves = vec![Rect { x: 0, y: 0, w: 1, h: 2 }, Rect { x: 1, y: 0, w: 1, h: 2 }];
+    let area: i32 = free_leaves(&build_quad_from_free(&halves, 0, 0, 4)).iter().map(|r| r.w * r.h).sum();
+    assert_eq!(area, 4);
+}
+
+#[test]
+fn test_pruned_build_matches_iterative_on_dense_map() {
+    // Overlapping and out-of-cell obstacles exercise the per-quadrant pruning
+    let mut obs = Vec::new();
+    for i in 0..40 {
+        obs.push(Rect { x: (i * 7) % 60, y: (i * 13) % 60, w: 1 + i % 4, h: 1 + (i * 3) % 5 });
+    }
+    assert_eq!(build_quad(&obs, 0, 0, 64), build_quad_iterative(&obs, 0, 0, 64));
+}
+
+#[test]
+fn test_validate_accepts_built_tree() {
+    let obs = vec![Rect { x: 2, y: 2, w: 2, h: 4 }, Rect { x: 5, y: 0, w: 1, h: 7 }];
+    assert_eq!(validate(&build_quad(&obs, 0, 0, 8), 8), Ok(()));
+}
+
+#[test]
+fn test_validate_rejects_misplaced_leaf_and_odd_split() {
+    let misplaced = Quad::Split(Box::new([
+        Quad::Free(0, 2, 2),
+        Quad::Free(2, 2, 2),
+        Quad::Free(2, 0, 2),
+        Quad::Blocked,
+    ]));
+    assert!(validate(&misplaced, 4).is_err());
+
+    let odd = Quad::Split(Box::new([Quad::Blocked, Quad::Blocked, Quad::Blocked, Quad::Blocked]));
+    assert!(validate(&odd, 3).is_err());
+}
+
+#[test]
+fn test_merge_free_leaves_collapses_fully_free_splits() {
+    // Two halves of the grid listed as separate free rectangles
+    let halves = vec![Rect { x: 0, y: 0, w: 2, h: 4 }, Rect { x: 2, y: 0, w: 2, h: 4 }];
+    let quad = build_quad_from_free(&halves, 0, 0, 4);
+    assert!(matches!(quad, Quad::Split(_)));
+    assert_eq!(merge_free_leaves(quad), Quad::Free(0, 0, 4));
+
+    // A split with a blocked child is left alone
+    let obs = vec![Rect { x: 0, y: 0, w: 2, h: 2 }];
+    let quad = build_quad(&obs, 0, 0, 4);
+    assert_eq!(merge_free_leaves(quad.clone()), quad);
+}
+
+#[test]
+fn test_auto_build_matches_either_strategy() {
+    let obs = vec![Rect { x: 2, y: 2, w: 2, h: 4 }, Rect { x: 3, y: 1, w: 3, h: 1 }];
+    let expected = build_quad(&obs, 0, 0, 16);
+    assert_eq!(build_quad_auto(&obs, 0, 0, 16, RECURSIVE_BUILD_LIMIT), expected);
+    assert_eq!(build_quad_auto(&obs, 0, 0, 16, 8), expected);
+}
+
+#[test]
+fn test_build_quad_window_blocks_outside() {
+    let obs = vec![Rect { x: 12, y: 12, w: 2, h: 2 }, Rect { x: 0, y: 0, w: 4, h: 4 }];
+    let window = Rect { x: 10, y: 10, w: 6, h: 5 };
+    let quad = build_quad_window(&obs, window);
+
+    let leaves = free_leaves(&quad);
+    let area: i32 = leaves.iter().map(|r| r.w * r.h).sum();
+    assert_eq!(area, 6 * 5 - 4);
+    for r in &leaves {
+        assert!(r.x >= 10 && r.y >= 10 && r.x + r.w <= 16 && r.y + r.h <= 15);
+    }
+}
+
+#[test]
+fn test_raycast_stops_at_obstacles_and_edges() {
+    let quad = build_quad(&[Rect { x: 5, y: 0, w: 1, h: 4 }], 0, 0, 8);
+    let close = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).abs() < 1e-6 && (a.1 - b.1).abs() < 1e-6;
+
+    assert!(close(raycast(&quad, (0.5, 1.5), (1.0, 0.0)).unwrap(), (5.0, 1.5)));
+    assert!(close(raycast(&quad, (0.5, 6.5), (1.0, 0.0)).unwrap(), (8.0, 6.5)));
+    assert!(close(raycast(&quad, (1.0, 1.0), (1.0, 1.0)).unwrap(), (8.0, 8.0)));
+    assert!(close(raycast(&quad, (7.5, 2.0), (-1.0, 0.0)).unwrap(), (6.0, 2.0)));
+
+    assert_eq!(raycast(&quad, (5.5, 1.0), (1.0, 0.0)), None);
+    assert_eq!(raycast(&quad, (1.0, 1.0), (0.0, 0.0)), None);
+}
+
+#[test]
+fn test_segment_is_free_edges_and_borders() {
+    let quad = build_quad(&[Rect { x: 2, y: 2, w: 2, h: 2 }], 0, 0, 8);
+    assert!(segment_is_free(&quad, (0.5, 0.5), (7.5, 0.5)));
+    assert!(!segment_is_free(&quad, (0.5, 0.5), (7.5, 7.5)));
+    // Grazing the obstacle's top edge stays free; leaving the grid does not
+    assert!(segment_is_free(&quad, (0.0, 4.0), (8.0, 4.0)));
+    assert!(!segment_is_free(&quad, (6.0, 6.0), (9.0, 6.0)));
+}
+
+proptest! {
+    #[test]
+    fn prop_segment_is_free_matches_obstacle_scan(
+        obstacles in prop::collection::vec((0i32..16, 0i32..16, 1i32..6, 1i32..6), 0..6),
+        a in (0.0f64..16.0, 0.0f64..16.0),
+        b in (0.0f64..16.0, 0.0f64..16.0),
+    ) {
+        let obstacles: Vec<Rect> = obstacles
+            .into_iter()
+            .map(|(x, y, w, h)| Rect { x, y, w: w.min(16 - x), h: h.min(16 - y) })
+            .collect();
+        let quad = build_quad(&obstacles, 0, 0, 16);
+        let naive = !obstacles.iter().any(|obs| segment_intersects(obs, a, b));
+        prop_assert_eq!(segment_is_free(&quad, a, b), naive);
+    }
+}
+
+#[test]
+fn test_morton_leaves_are_contiguous_in_z_order() {
+    assert_eq!(morton_code(0, 0), 0);
+    assert_eq!(morton_code(1, 0), 1);
+    assert_eq!(morton_code(0, 1), 2);
+    assert_eq!(morton_code(3, 5), 0b100111);
+
+    let quad = build_quad(&[Rect { x: 1, y: 1, w: 1, h: 1 }], 0, 0, 8);
+    let cells = morton_leaves(&quad);
+    assert_eq!(cells.len(), free_leaves(&quad).len());
+    // Free leaves tile everything but the blocked cell at code 3
+    let mut next = 0;
+    for (code, r) in cells {
+        if next == 3 {
+            next += 1;
+        }
+        assert_eq!(code, next);
+        next += (r.w * r.h) as u64;
+    }
+    assert_eq!(next, 64);
+}
+
+#[test]
+fn test_leaf_neighbors_match_shared_borders() {
+    let quad = build_quad(&[Rect { x: 2, y: 2, w: 1, h: 3 }, Rect { x: 9, y: 5, w: 4, h: 2 }], 0, 0, 16);
+    let leaves = free_leaves(&quad);
+    for leaf in &leaves {
+        let mut found = leaf_neighbors(&quad, leaf);
+        let mut expected: Vec<Rect> =
+            leaves.iter().filter(|r| shared_border(leaf, r).is_some()).copied().collect();
+        found.sort_by_key(|r| (r.x, r.y));
+        expected.sort_by_key(|r| (r.x, r.y));
+        assert_eq!(found, expected);
+    }
+
+    assert_eq!(leaf_at_point(&quad, 2, 3), None);
+    assert_eq!(leaf_at_point(&quad, 16, 0), None);
+    assert_eq!(leaf_at_point(&quad, 12, 12), Some(Rect { x: 8, y: 8, w: 8, h: 8 }));
+}
+
+#[test]
+fn test_quad_diff_reports_structure_and_state_changes() {
+    let old = build_quad(&[Rect { x: 4, y: 4, w: 4, h: 4 }], 0, 0, 8);
+    let new = build_quad(&[Rect { x: 0, y: 0, w: 1, h: 1 }], 0, 0, 8);
+    let diff = quad_diff(&old, &new);
+
+    assert!(diff.contains(&(Rect { x: 4, y: 4, w: 4, h: 4 }, Change::BecameFree)));
+    assert!(diff.contains(&(Rect { x: 0, y: 0, w: 4, h: 4 }, Change::Subdivided)));
+    assert!(diff.contains(&(Rect { x: 0, y: 0, w: 1, h: 1 }, Change::BecameBlocked)));
+    let changed: i32 = diff
+        .iter()
+        .filter(|(_, c)| matches!(c, Change::BecameFree | Change::BecameBlocked))
+        .map(|(r, _)| r.w * r.h)
+        .sum();
+    assert_eq!(changed, 17);
+
+    assert!(quad_diff(&old, &old).is_empty());
+    let merged = quad_diff(&new, &Quad::Free(0, 0, 8));
+    assert_eq!(merged[0], (Rect { x: 0, y: 0, w: 8, h: 8 }, Change::Merged));
+}
+
+#[test]
+fn test_adaptive_build_is_coarse_in_open_space() {
+    // One isolated obstacle and a cluster of four in the 8x8 cell at (16, 16)
+    let obstacles = [
+        Rect { x: 2, y: 2, w: 1, h: 1 },
+        Rect { x: 17, y: 17, w: 1, h: 1 },
+        Rect { x: 19, y: 18, w: 1, h: 1 },
+        Rect { x: 21, y: 19, w: 1, h: 1 },
+        Rect { x: 18, y: 21, w: 1, h: 1 },
+    ];
+    let table = [(1, 8), (3, 1)];
+    assert_eq!(density_min_side(&table, 1), 8);
+    assert_eq!(density_min_side(&table, 4), 1);
+    assert_eq!(density_min_side(&[(2, 4)], 1), 1);
+
+    let adaptive = build_quad_adaptive(&obstacles, 0, 0, 32, &|k| density_min_side(&table, k));
+    let full = build_quad(&obstacles, 0, 0, 32);
+    let leaves = free_leaves(&adaptive);
+    assert!(leaves.len() < free_leaves(&full).len());
+    // The isolated obstacle blocks its whole 8x8 cell, the cluster is split finer
+    assert_eq!(leaf_at_point(&adaptive, 5, 5), None);
+    assert_eq!(leaf_at_point(&adaptive, 21, 21), Some(Rect { x: 20, y: 20, w: 4, h: 4 }));
+    // Nothing blocked in the full tree becomes free
+    assert!(leaves.iter().all(|r| obstacles.iter().all(|o| !intersects(o, r.x, r.y, r.w))));
+}
+
+#[test]
+fn test_smallest_enclosing_node_rebuilds_locally() {
+    let mut obstacles = vec![Rect { x: 1, y: 1, w: 2, h: 2 }];
+    let mut quad = build_quad(&obstacles, 0, 0, 16);
+    let added = Rect { x: 10, y: 9, w: 1, h: 2 };
+    obstacles.push(added);
+
+    let (node, extent) = smallest_enclosing_node_mut(&mut quad, 0, 0, 16, &added).unwrap();
+    // The NE quadrant was still a single free leaf
+    assert_eq!(extent, Rect { x: 8, y: 8, w: 8, h: 8 });
+    *node = build_quad(&obstacles, extent.x, extent.y, extent.w);
+    assert_eq!(quad, build_quad(&obstacles, 0, 0, 16));
+
+    // Straddling the center leaves only the root
+    let (_, root) = smallest_enclosing_node_mut(&mut quad, 0, 0, 16, &Rect { x: 7, y: 7, w: 2, h: 2 }).unwrap();
+    assert_eq!(root, Rect { x: 0, y: 0, w: 16, h: 16 });
+    assert!(smallest_enclosing_node_mut(&mut quad, 0, 0, 16, &Rect { x: 15, y: 0, w: 2, h: 1 }).is_none());
+}
+
+#[test]
+fn test_smallest_enclosing_node_in_blocked_tree() {
+    let mut obstacles = vec![Rect { x: 0, y: 0, w: 8, h: 8 }];
+    let mut quad = build_quad(&obstacles, 0, 0, 8);
+    assert_eq!(quad, Quad::Blocked);
+
+    // Shrinking the obstacle frees a corner: the blocked root is the node to rebuild
+    obstacles[0] = Rect { x: 0, y: 0, w: 8, h: 6 };
+    let (node, extent) = smallest_enclosing_node_mut(&mut quad, 0, 0, 8, &Rect { x: 0, y: 6, w: 8, h: 2 }).unwrap();
+    assert_eq!(extent, Rect { x: 0, y: 0, w: 8, h: 8 });
+    *node = build_quad(&obstacles, extent.x, extent.y, extent.w);
+    assert_eq!(quad, build_quad(&obstacles, 0, 0, 8));
+}
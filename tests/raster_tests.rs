@@ -0,0 +1,26 @@
+#![cfg(feature = "png")]
+
+use veloquad::models::rect::Rect;
+use veloquad::planner::{PlanOptions, Planner};
+use veloquad::raster::write_png;
+
+#[test]
+fn test_write_png_draws_obstacles_and_path() {
+    let planner = Planner::new(8, &[Rect { x: 2, y: 2, w: 2, h: 4 }], &PlanOptions::default()).unwrap();
+    let plan = planner.query((0, 0), (7, 7)).unwrap();
+    let path = std::env::temp_dir().join(format!("veloquad_raster_{}.png", std::process::id()));
+    let path = path.to_str().unwrap();
+    write_png(path, &planner, Some(&plan.waypoints), 4).unwrap();
+
+    let decoder = png::Decoder::new(std::fs::File::open(path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!((info.width, info.height), (32, 32));
+
+    // Rows run top to bottom: map cell (2, 2) is pixel (8, 32 - 1 - 8)
+    let at = |x: usize, y: usize| &pixels[3 * ((31 - y) * 32 + x)..][..3];
+    assert_eq!(at(9, 9), [0x33, 0x33, 0x33]);
+    assert_eq!(at(30, 2), [255, 255, 255]);
+    assert!(pixels.chunks(3).any(|p| p == [255, 0, 0]));
+}
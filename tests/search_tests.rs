@@ -0,0 +1,110 @@
+use proptest::prelude::*;
+use veloquad::algo::{astar, bidirectional_dijkstra, dijkstra_distances, dijkstra_lazy, dijkstra_path};
+use veloquad::geometry::{covers, shared_border};
+use veloquad::models::rect::Rect;
+use veloquad::planner::{PlanOptions, Planner};
+use veloquad::quadtree::build_quad;
+
+const EPS: f64 = 1e-9;
+
+/// Obstacles scattered over a 32×32 grid, clipped to it.
+fn random_map() -> impl Strategy<Value = Vec<Rect>> {
+    prop::collection::vec((0i32..32, 0i32..32, 1i32..10, 1i32..10), 0..12).prop_map(|rects| {
+        rects
+            .into_iter()
+            .map(|(x, y, w, h)| Rect { x, y, w: w.min(32 - x), h: h.min(32 - y) })
+            .collect()
+    })
+}
+
+proptest! {
+    #[test]
+    fn prop_searches_agree_on_optimal_cost(
+        obstacles in random_map(),
+        selector in (any::<prop::sample::Index>(), any::<prop::sample::Index>()),
+    ) {
+        let Ok(planner) = Planner::new(32, &obstacles, &PlanOptions::default()) else {
+            return Ok(());
+        };
+        let (graph, centers) = (planner.graph(), planner.centers());
+        let (start, goal) = (selector.0.index(graph.len()), selector.1.index(graph.len()));
+
+        let dijkstra = dijkstra_path(graph, start, goal).map(|r| r.cost);
+        let guided = astar(graph, centers, start, goal, (1.0, 1.0)).map(|r| r.cost);
+        let both = bidirectional_dijkstra(graph, start, goal).map(|r| r.cost);
+        match dijkstra {
+            None => {
+                prop_assert!(guided.is_none());
+                prop_assert!(both.is_none());
+            }
+            Some(cost) => {
+                prop_assert!((guided.unwrap() - cost).abs() < EPS);
+                prop_assert!((both.unwrap() - cost).abs() < EPS);
+            }
+        }
+
+        // The A* heuristic never overestimates the remaining distance
+        let (gx, gy) = centers[&goal];
+        for (node, remaining) in dijkstra_distances(graph, goal).into_iter().enumerate() {
+            let (x, y) = centers[&node];
+            prop_assert!(((x - gx).powi(2) + (y - gy).powi(2)).sqrt() <= remaining + EPS);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn prop_lazy_search_matches_region_graph(
+        obstacles in random_map(),
+        start in (0i32..32, 0i32..32),
+        goal in (0i32..32, 0i32..32),
+    ) {
+        let quad = build_quad(&obstacles, 0, 0, 32);
+        let blocked = |(x, y): (i32, i32)| obstacles.iter().any(|r| covers(r, x, y, 1));
+        let lazy = dijkstra_lazy(&quad, start, goal);
+        if blocked(start) || blocked(goal) {
+            prop_assert!(lazy.is_none());
+            return Ok(());
+        }
+
+        // Compare on the regions containing the points; `query` snaps to centers instead
+        let planner = Planner::new(32, &obstacles, &PlanOptions::default()).unwrap();
+        let containing = |(x, y): (i32, i32)| planner.regions().iter().position(|r| covers(r, x, y, 1)).unwrap();
+        let full = dijkstra_path(planner.graph(), containing(start), containing(goal));
+        match (full, lazy) {
+            (Some(full), Some((cost, leaves))) => {
+                prop_assert!((full.cost - cost).abs() < EPS);
+                prop_assert!(leaves.windows(2).all(|p| shared_border(&p[0], &p[1]).is_some()));
+            }
+            (None, None) => {}
+            (full, lazy) => prop_assert!(false, "{:?} vs {:?}", full.map(|p| p.cost), lazy),
+        }
+    }
+}
+
+#[test]
+fn test_bidirectional_path_is_connected() {
+    let obstacles = [Rect { x: 10, y: 0, w: 2, h: 28 }, Rect { x: 20, y: 4, w: 2, h: 28 }];
+    let planner = Planner::new(32, &obstacles, &PlanOptions::default()).unwrap();
+    let graph = planner.graph();
+    let goal = graph.len() - 1;
+    let found = bidirectional_dijkstra(graph, 0, goal).unwrap();
+
+    assert_eq!((found.path[0], *found.path.last().unwrap()), (0, goal));
+    let mut cost = 0.0;
+    for pair in found.path.windows(2) {
+        cost += graph[pair[0]].iter().find(|&&(to, _)| to == pair[1]).unwrap().1;
+    }
+    assert!((cost - found.cost).abs() < EPS);
+    assert!((dijkstra_path(graph, 0, goal).unwrap().cost - found.cost).abs() < EPS);
+}
+
+#[test]
+fn test_bidirectional_follows_directed_weights() {
+    // 0 -> 1 -> 2 is cheap one way; the reverse edges are expensive
+    let graph = vec![vec![(1, 1.0), (2, 11.0)], vec![(0, 10.0), (2, 1.0)], vec![(1, 10.0), (0, 11.0)]];
+    let both = bidirectional_dijkstra(&graph, 0, 2).unwrap();
+    assert_eq!(both.cost, 2.0);
+    assert_eq!(both.path, vec![0, 1, 2]);
+    assert_eq!(bidirectional_dijkstra(&graph, 2, 0).unwrap().cost, dijkstra_path(&graph, 2, 0).unwrap().cost);
+}
@@ -0,0 +1,16 @@
+#![cfg(feature = "serde")]
+
+use veloquad::models::rect::Rect;
+use veloquad::planner::{PlanOptions, PlanResult, Planner};
+
+#[test]
+fn test_plan_result_round_trips_through_json() {
+    let obstacles = [Rect { x: 2, y: 2, w: 2, h: 4 }, Rect { x: 3, y: 1, w: 3, h: 1 }];
+    let options = PlanOptions { border_crossings: true, ..PlanOptions::default() };
+    let result = Planner::new(8, &obstacles, &options).unwrap().query((0, 0), (7, 7)).unwrap();
+
+    let json = serde_json::to_string(&result).unwrap();
+    let back: PlanResult = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, result);
+    assert!(json.contains("\"obstacles\":[{\"x\":2,\"y\":2,\"w\":2,\"h\":4}"));
+}
@@ -0,0 +1,27 @@
+use veloquad::input::read_input_str;
+use veloquad::models::rect::Rect;
+use veloquad::planner::{PlanOptions, Planner};
+use veloquad::svg::render_svg;
+
+#[test]
+fn test_render_svg_draws_obstacles_and_path() {
+    let planner = Planner::new(8, &[Rect { x: 2, y: 2, w: 2, h: 4 }], &PlanOptions::default()).unwrap();
+    let plan = planner.query((0, 0), (7, 7)).unwrap();
+    let svg = render_svg(&planner, &[], Some(&plan.waypoints), 10.0);
+
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains(r#"width="80""#));
+    assert!(svg.contains(r##"<rect x="2" y="2" width="2" height="4" fill="#333"/>"##));
+    assert!(svg.contains("<polyline"));
+    assert_eq!(svg.matches("stroke=\"#9ab\"").count(), planner.regions().len());
+}
+
+#[test]
+fn test_render_svg_tags_labeled_obstacles() {
+    let map = read_input_str("8\n2\n1 1 2 2 wall\n5 5 1 1\n").unwrap();
+    let planner = Planner::new(map.n, &map.obstacles, &PlanOptions::default()).unwrap();
+    let svg = render_svg(&planner, &map.obstacle_labels, None, 10.0);
+
+    assert!(svg.contains(r#"class="wall" data-label="wall""#));
+    assert_eq!(svg.matches("data-label").count(), 1);
+}